@@ -1,16 +1,431 @@
-use clap::Parser;
+use std::str::FromStr;
+
+use clap::{Parser, ValueEnum};
+use maze_exit_lib::{
+    algorithm::{QueueKind, TieBreak},
+    generator::DiagonalMovement,
+    heuristics::{DEFAULT_TIE_BREAK, LandmarkSelection},
+};
 
 #[derive(Parser, Debug)]
 #[command(
-    author="Davide C. <davide.canton5@gmail.com>", 
-    version="1.0", 
-    about="A* algorithm for solving mazes", 
+    author="Davide C. <davide.canton5@gmail.com>",
+    version="1.0",
+    about="A* algorithm for solving mazes",
     long_about = None
 )]
 pub struct Args {
     pub img_path: String,
+    #[command(flatten)]
+    pub import: ImportArgs,
+    #[command(flatten)]
+    pub search: SearchArgs,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Algorithm {
+    #[default]
+    AStar,
+    /// A* with the heuristic inflated by `--epsilon`
+    WeightedAStar,
+    Dijkstra,
+    /// Breadth-first search, ignoring costs
+    Bfs,
+    /// Greedy best-first search
+    Greedy,
+    /// A* from both the start and the goal
+    Bidirectional,
+    /// Iterative deepening A*
+    IdaStar,
+    /// Memory-bounded A*, keeping at most `--max-nodes` nodes
+    SmaStar,
+    /// Anytime repairing A*, lowering the weight from `--epsilon` by `--epsilon-step`
+    AraStar,
+    /// Any-angle A*, see `--densify`
+    ThetaStar,
+    /// Any-angle A* checking line of sight only on expansion, see `--densify`
+    LazyThetaStar,
+    /// Hierarchical A* over clusters of `--cluster-size` cells, near-optimal
+    HpaStar,
+    /// Exact search over a simple subgoal graph, needs uniform costs and
+    /// `--diagonal if-no-obstacles`
+    Subgoal,
+}
+
+/// How the neighbours of a cell are generated.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Generator {
+    /// Jump point search, 8-connected
+    #[default]
+    Jps,
+    /// Jump point search scanning 64 cells at a time, 8-connected
+    BitJps,
+    /// Jump point search reading precomputed jump distances, 8-connected
+    JpsPlus,
+    /// Jump point search, 4-connected
+    Jps4,
+    /// Every orthogonal neighbour
+    Four,
+    /// Every orthogonal and diagonal neighbour
+    Eight,
+}
+
+/// Priority queue of the open list, see [`QueueKind`].
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Queue {
+    /// Binary heap
+    Binary,
+    /// Heap with four children per node
+    #[default]
+    Quaternary,
+    /// Pairing heap
+    Pairing,
+    /// Radix heap, exact only for monotone keys such as A* with a consistent heuristic
+    Radix,
+    /// Bucket queue with one bucket per step of cost, fastest for monotone keys
+    Bucket,
+}
+
+impl From<Queue> for QueueKind {
+    fn from(value: Queue) -> Self {
+        match value {
+            Queue::Binary => QueueKind::Binary,
+            Queue::Quaternary => QueueKind::Quaternary,
+            Queue::Pairing => QueueKind::Pairing,
+            Queue::Radix => QueueKind::Radix,
+            Queue::Bucket => QueueKind::Bucket,
+        }
+    }
+}
+
+/// Order of the nodes of equal priority, see [`TieBreak`].
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TieOrder {
+    /// Farthest from the start first
+    #[default]
+    HigherG,
+    /// Lowest heuristic first
+    LowerH,
+    /// Last reached first
+    Lifo,
+    /// First reached first
+    Fifo,
+    /// Closest to the line from the start to the goal first
+    CrossProduct,
+}
+
+impl From<TieOrder> for TieBreak {
+    fn from(value: TieOrder) -> Self {
+        match value {
+            TieOrder::HigherG => TieBreak::HigherG,
+            TieOrder::LowerH => TieBreak::LowerH,
+            TieOrder::Lifo => TieBreak::Lifo,
+            TieOrder::Fifo => TieBreak::Fifo,
+            TieOrder::CrossProduct => TieBreak::CrossProduct,
+        }
+    }
+}
+
+/// When diagonal moves are allowed, see [`DiagonalMovement`].
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Diagonal {
+    Always,
+    Never,
+    /// Only if both orthogonal cells are free
+    IfNoObstacles,
+    /// Unless both orthogonal cells are walls
+    #[default]
+    IfAtMostOneObstacle,
+}
+
+impl From<Diagonal> for DiagonalMovement {
+    fn from(value: Diagonal) -> Self {
+        match value {
+            Diagonal::Always => DiagonalMovement::Always,
+            Diagonal::Never => DiagonalMovement::Never,
+            Diagonal::IfNoObstacles => DiagonalMovement::IfNoObstacles,
+            Diagonal::IfAtMostOneObstacle => DiagonalMovement::IfAtMostOneObstacle,
+        }
+    }
+}
+
+/// Estimate of the cost to the goal guiding the searches.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Heuristic {
+    /// Octile distance scaled by `--heuristic-factor`
+    #[default]
+    Diagonal,
+    /// Exact octile distance
+    Octile,
+    Manhattan,
+    Euclidean,
+    Chebyshev,
+    /// ALT: triangle inequality over the distances to `--landmarks` landmarks
+    Landmarks,
+}
+
+/// Where the landmarks of the ALT heuristic are placed, see [`LandmarkSelection`].
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Selection {
+    /// Each landmark as far as possible from the others
+    #[default]
+    Farthest,
+    /// Landmarks spread along the border
+    Corners,
+}
+
+impl From<Selection> for LandmarkSelection {
+    fn from(value: Selection) -> Self {
+        match value {
+            Selection::Farthest => LandmarkSelection::Farthest,
+            Selection::Corners => LandmarkSelection::Corners,
+        }
+    }
+}
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct SearchArgs {
+    #[arg(short = 'a', long, value_enum, default_value_t = Algorithm::AStar)]
+    pub algorithm: Algorithm,
+    #[arg(short = 'g', long, value_enum, default_value_t = Generator::Jps)]
+    pub generator: Generator,
+    /// Priority queue of the open list of A*, weighted A*, Dijkstra, greedy and HPA*
+    #[arg(long, value_enum, default_value_t = Queue::Quaternary)]
+    pub queue: Queue,
+    /// Order of the nodes of equal priority of A*, weighted A*, Dijkstra and greedy
+    #[arg(long = "tie-order", value_enum, default_value_t = TieOrder::HigherG)]
+    pub tie_order: TieOrder,
+    /// Diagonal moves allowed by the 8-connected generators
+    #[arg(long, value_enum, default_value_t = Diagonal::IfAtMostOneObstacle)]
+    pub diagonal: Diagonal,
+    /// Heuristic weight of weighted A*
+    #[arg(long, default_value_t = 1.5)]
+    pub epsilon: f64,
+    /// Weight decrement between the searches of ARA*
+    #[arg(long = "epsilon-step", default_value_t = 0.25)]
+    pub epsilon_step: f64,
+    /// Node cap of SMA*
+    #[arg(long = "max-nodes", default_value_t = 100_000)]
+    pub max_nodes: usize,
+    /// Return every cell crossed by an any-angle path instead of its waypoints
+    #[arg(long)]
+    pub densify: bool,
+    /// Side of the clusters of HPA*
+    #[arg(long = "cluster-size", default_value_t = 32)]
+    pub cluster_size: u32,
+    #[arg(long, value_enum, default_value_t = Heuristic::Diagonal)]
+    pub heuristic: Heuristic,
+    /// Factor of the diagonal heuristic only, above 1 to favour the nodes closer to the goal
+    /// among those of equal priority
+    #[arg(long = "heuristic-factor", default_value_t = DEFAULT_TIE_BREAK)]
+    pub heuristic_factor: f64,
+    /// Weight multiplying the heuristic
+    #[arg(long = "heuristic-weight", default_value_t = 1.0)]
+    pub heuristic_weight: f64,
+    /// Landmarks of the ALT heuristic
+    #[arg(long, default_value_t = 8)]
+    pub landmarks: usize,
+    /// Where the landmarks of the ALT heuristic are placed
+    #[arg(long = "landmark-selection", value_enum, default_value_t = Selection::Farthest)]
+    pub landmark_selection: Selection,
+}
+
+impl SearchArgs {
+    /// The diagonal policy actually followed by the chosen generator.
+    pub fn diagonal_movement(&self) -> DiagonalMovement {
+        match self.generator {
+            Generator::Jps4 | Generator::Four => DiagonalMovement::Never,
+            Generator::Jps | Generator::BitJps | Generator::JpsPlus | Generator::Eight => {
+                self.diagonal.into()
+            }
+        }
+    }
+}
+
+/// How the image reader turns free pixels into cell costs.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ImportMode {
+    /// Every free pixel costs 1.
+    #[default]
+    Classify,
+    /// Darker pixels cost more, up to `--max-cost` for black. There are no walls.
+    Luminance,
+    /// Pixels take the cost of the closest `--palette` entry.
+    Palette,
+}
+
+pub const DEFAULT_THRESHOLD: f64 = 250.0;
+pub const DEFAULT_MAX_COST: f64 = 10.0;
+pub const DEFAULT_WALL_COLOR: Color = Color([0, 0, 0]);
+pub const DEFAULT_START_COLOR: Color = Color([255, 0, 0]);
+pub const DEFAULT_GOAL_COLOR: Color = Color([0, 255, 0]);
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct ImportArgs {
+    /// How free pixels of an image are mapped to cell costs
+    #[arg(long = "import-mode", value_enum, default_value_t = ImportMode::Classify)]
+    pub mode: ImportMode,
+    /// Maximum colour difference for a pixel to match a wall, start, goal or palette colour
+    #[arg(long, default_value_t = DEFAULT_THRESHOLD)]
+    pub threshold: f64,
+    #[arg(long = "wall-color", default_value_t = DEFAULT_WALL_COLOR)]
+    pub wall_color: Color,
+    #[arg(long = "start-color", default_value_t = DEFAULT_START_COLOR)]
+    pub start_color: Color,
+    #[arg(long = "goal-color", default_value_t = DEFAULT_GOAL_COLOR)]
+    pub goal_color: Color,
+    /// Cost of a black pixel in luminance mode, white pixels cost 1
+    #[arg(long = "max-cost", default_value_t = DEFAULT_MAX_COST, value_parser = parse_cost)]
+    pub max_cost: f64,
+    /// Palette entries in the form `R,G,B=COST` or `#RRGGBB=COST`
+    #[arg(long = "palette", value_name = "COLOR=COST")]
+    pub palette: Vec<PaletteEntry>,
+}
+
+impl Default for ImportArgs {
+    fn default() -> Self {
+        ImportArgs {
+            mode: ImportMode::default(),
+            threshold: DEFAULT_THRESHOLD,
+            wall_color: DEFAULT_WALL_COLOR,
+            start_color: DEFAULT_START_COLOR,
+            goal_color: DEFAULT_GOAL_COLOR,
+            max_cost: DEFAULT_MAX_COST,
+            palette: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Color(pub [u8; 3]);
+
+impl FromStr for Color {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(hex) = s.strip_prefix('#') {
+            // from_str_radix would accept a sign
+            if hex.len() != 6 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return Err(format!("Invalid hex color {s}"));
+            }
+            let value =
+                u32::from_str_radix(hex, 16).map_err(|e| format!("Invalid color {s}: {e}"))?;
+            let [_, r, g, b] = value.to_be_bytes();
+            return Ok(Color([r, g, b]));
+        }
+
+        let parts = s
+            .split(',')
+            .map(|p| {
+                let p = p.trim();
+                if p.is_empty() || !p.bytes().all(|b| b.is_ascii_digit()) {
+                    return Err(format!("Invalid color {s}: {p:?} is not a number"));
+                }
+                p.parse::<u8>()
+                    .map_err(|e| format!("Invalid color {s}: {e}"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match parts[..] {
+            [r, g, b] => Ok(Color([r, g, b])),
+            _ => Err(format!("Invalid color {s}: expected R,G,B")),
+        }
+    }
+}
+
+impl std::fmt::Display for Color {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let [r, g, b] = self.0;
+        write!(f, "{r},{g},{b}")
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PaletteEntry {
+    pub color: Color,
+    pub cost: f64,
+}
+
+impl FromStr for PaletteEntry {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (color, cost) = s
+            .split_once('=')
+            .ok_or_else(|| format!("Invalid palette entry {s}: expected COLOR=COST"))?;
+        let cost = parse_cost(cost.trim())?;
+
+        Ok(PaletteEntry {
+            color: color.parse()?,
+            cost,
+        })
+    }
+}
+
+/// Parses a cell cost, which must be finite and above 0.
+fn parse_cost(s: &str) -> Result<f64, String> {
+    let cost: f64 = s.parse().map_err(|e| format!("Invalid cost {s}: {e}"))?;
+    if cost.is_finite() && cost > 0.0 {
+        Ok(cost)
+    } else {
+        Err(format!(
+            "Invalid cost {s}: expected a finite number above 0"
+        ))
+    }
 }
 
 pub fn parse_args<T: Parser>() -> T {
     T::parse()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_color() {
+        assert_eq!("1, 2,3".parse(), Ok(Color([1, 2, 3])));
+        assert_eq!("#ff8000".parse(), Ok(Color([255, 128, 0])));
+        assert!("1,2".parse::<Color>().is_err());
+        assert!("#ff80".parse::<Color>().is_err());
+        assert!("#+fffff".parse::<Color>().is_err());
+        assert!("+1,2,3".parse::<Color>().is_err());
+    }
+
+    #[test]
+    fn parse_palette_entry() {
+        let entry: PaletteEntry = "#0000ff=4.5".parse().unwrap();
+        assert_eq!(entry.color, Color([0, 0, 255]));
+        assert_eq!(entry.cost, 4.5);
+        assert!("0,0,255".parse::<PaletteEntry>().is_err());
+        assert!("0,0,255=0".parse::<PaletteEntry>().is_err());
+        assert!("0,0,255=-2".parse::<PaletteEntry>().is_err());
+        assert!("0,0,255=inf".parse::<PaletteEntry>().is_err());
+    }
+
+    #[test]
+    fn parse_max_cost() {
+        let args = Args::try_parse_from(["maze", "a.png", "--max-cost", "2.5"]).unwrap();
+        assert_eq!(args.import.max_cost, 2.5);
+        assert!(Args::try_parse_from(["maze", "a.png", "--max-cost", "0"]).is_err());
+        assert!(Args::try_parse_from(["maze", "a.png", "--max-cost", "NaN"]).is_err());
+    }
+
+    #[test]
+    fn parse_tie_options() {
+        let args = [
+            "maze",
+            "a.png",
+            "--tie-order",
+            "fifo",
+            "--heuristic-factor",
+            "1",
+        ];
+        let args = Args::try_parse_from(args).unwrap();
+        assert_eq!(args.search.tie_order, TieOrder::Fifo);
+        assert_eq!(args.search.heuristic_factor, 1.0);
+        assert!(Args::try_parse_from(["maze", "a.png", "--ties", "fifo"]).is_err());
+        assert!(Args::try_parse_from(["maze", "a.png", "--tie-break", "1"]).is_err());
+    }
+}
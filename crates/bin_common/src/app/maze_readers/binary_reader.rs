@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{Result, anyhow, bail};
 use byteorder::{BigEndian, ReadBytesExt};
 use maze_exit_lib::{
     maze::Maze,
@@ -57,7 +57,6 @@ impl MazeBinaryReader {
 }
 
 impl MazeReader for MazeBinaryReader {
-    #[allow(clippy::unbuffered_bytes)]
     fn read_maze(&self, reader: impl Read + Seek) -> Result<Maze> {
         let mut builder = MazeBuilder::new();
 
@@ -67,12 +66,14 @@ impl MazeReader for MazeBinaryReader {
         let h = decoder.read_u32::<BigEndian>()?;
         let mut x = 0;
         let mut y = 0;
-        let mut remaining = w * h;
+        let mut remaining = w as u64 * h as u64;
 
         builder = builder.width(w).height(h);
 
-        for b in decoder.bytes() {
-            for v in read_cell(b?) {
+        let mut cells = vec![0; remaining.div_ceil(4) as usize];
+        decoder.read_exact(&mut cells)?;
+        for b in cells {
+            for v in read_cell(b) {
                 if remaining == 0 {
                     break;
                 }
@@ -92,7 +93,24 @@ impl MazeReader for MazeBinaryReader {
             }
         }
 
-        builder.build()
+        let mut maze = builder.build()?;
+
+        // files of mazes without costs end with the cells
+        let mut costs = Vec::new();
+        decoder.read_to_end(&mut costs)?;
+        if !costs.is_empty() {
+            if costs.len() as u64 != w as u64 * h as u64 * 4 {
+                bail!("Invalid costs: {} bytes for {}x{} cells", costs.len(), w, h);
+            }
+            for (i, bytes) in costs.chunks_exact(4).enumerate() {
+                let (x, y) = (i as u64 % w as u64, i as u64 / w as u64);
+                let cost = f32::from_be_bytes(bytes.try_into()?);
+                maze.set_cost(Position::try_convert(x, y)?, cost as f64)
+                    .map_err(|e| anyhow!(e))?;
+            }
+        }
+
+        Ok(maze)
     }
 }
 
@@ -104,3 +122,52 @@ fn read_cell(b: u8) -> [Cell; 4] {
         (b & 0b11).try_into().unwrap(),
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Seek};
+
+    use super::*;
+    use crate::{BinaryMazeWriter, MazeWriter};
+
+    #[test]
+    fn read_wide_maze() {
+        let mut maze = Maze::new(40_000, 3, Position::new(0, 0), Position::new(39_999, 2));
+        maze.set(Position::new(35_000, 1), true).unwrap();
+
+        let mut buf = Cursor::new(Vec::new());
+        BinaryMazeWriter.write_maze(&maze, &mut buf).unwrap();
+        buf.rewind().unwrap();
+
+        assert!(MazeBinaryReader.check(&mut buf).unwrap());
+        let read = MazeBinaryReader.read_maze(buf).unwrap();
+
+        assert_eq!(read.width(), 40_000);
+        assert_eq!(read.goal(), Position::new(39_999, 2));
+        assert_eq!(
+            read.walls().collect::<Vec<_>>(),
+            &[Position::new(35_000, 1)]
+        );
+    }
+
+    #[test]
+    fn read_costs() {
+        let mut maze = Maze::new(5, 3, Position::new(0, 0), Position::new(4, 2));
+        maze.set(Position::new(2, 1), true).unwrap();
+        maze.set_cost(Position::new(1, 1), 2.5).unwrap();
+        maze.set_cost(Position::new(3, 0), 0.1).unwrap();
+
+        let mut buf = Cursor::new(Vec::new());
+        BinaryMazeWriter.write_maze(&maze, &mut buf).unwrap();
+        buf.rewind().unwrap();
+
+        assert!(MazeBinaryReader.check(&mut buf).unwrap());
+        let read = MazeBinaryReader.read_maze(buf).unwrap();
+
+        assert_eq!(
+            read.cost(Position::new(1, 1)),
+            maze.cost(Position::new(1, 1))
+        );
+        assert_eq!(read.fingerprint(), maze.fingerprint());
+    }
+}
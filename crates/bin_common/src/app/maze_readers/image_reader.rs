@@ -7,12 +7,13 @@ use maze_exit_lib::maze::Maze;
 use maze_exit_lib::maze_builder::MazeBuilder;
 
 use super::MazeReader;
+use crate::app::args::{Color, ImportArgs, ImportMode};
 
-const THRESHOLD: f64 = 250.0;
-
-pub(crate) struct MazeImageReader;
+pub(crate) struct MazeImageReader<'a> {
+    pub(crate) args: &'a ImportArgs,
+}
 
-impl MazeReader for MazeImageReader {
+impl MazeReader for MazeImageReader<'_> {
     fn read_maze(&self, reader: impl Read + Seek) -> Result<Maze> {
         let mut reader = ImageReader::new(BufReader::new(reader)).with_guessed_format()?;
         reader.no_limits();
@@ -25,12 +26,17 @@ impl MazeReader for MazeImageReader {
                 let p = *image.get_pixel(x, y);
                 let pos = Position::try_convert(x, y)?;
 
-                if is_wall(p) {
+                if self.is_wall(p) {
                     builder = builder.add_wall(pos);
-                } else if is_start(p) {
+                } else if self.is_start(p) {
                     builder = builder.start(pos);
-                } else if is_goal(p) {
+                } else if self.is_goal(p) {
                     builder = builder.goal(pos);
+                } else {
+                    let cost = self.cost(p);
+                    if cost != 1.0 {
+                        builder = builder.add_cost(pos, cost);
+                    }
                 }
             }
         }
@@ -39,16 +45,47 @@ impl MazeReader for MazeImageReader {
     }
 }
 
-fn is_goal(pixel: Rgb<u8>) -> bool {
-    color_difference(pixel.0, [0, 255, 0]) < THRESHOLD
-}
+impl MazeImageReader<'_> {
+    fn is_goal(&self, pixel: Rgb<u8>) -> bool {
+        self.matches(pixel, self.args.goal_color)
+    }
+
+    fn is_start(&self, pixel: Rgb<u8>) -> bool {
+        self.matches(pixel, self.args.start_color)
+    }
 
-fn is_start(pixel: Rgb<u8>) -> bool {
-    color_difference(pixel.0, [255, 0, 0]) < THRESHOLD
+    fn is_wall(&self, pixel: Rgb<u8>) -> bool {
+        // in luminance mode dark pixels are expensive cells, not walls
+        self.args.mode != ImportMode::Luminance && self.matches(pixel, self.args.wall_color)
+    }
+
+    fn matches(&self, pixel: Rgb<u8>, color: Color) -> bool {
+        color_difference(pixel.0, color.0) < self.args.threshold
+    }
+
+    /// Cost of a pixel that is neither a wall, the start nor the goal.
+    fn cost(&self, pixel: Rgb<u8>) -> f64 {
+        match self.args.mode {
+            ImportMode::Classify => 1.0,
+            ImportMode::Luminance => {
+                let darkness = 1.0 - luminance(pixel.0) / 255.0;
+                1.0 + darkness * (self.args.max_cost - 1.0)
+            }
+            ImportMode::Palette => self
+                .args
+                .palette
+                .iter()
+                .map(|e| (color_difference(pixel.0, e.color.0), e.cost))
+                .filter(|(d, _)| *d < self.args.threshold)
+                .min_by(|(d1, _), (d2, _)| d1.total_cmp(d2))
+                .map_or(1.0, |(_, cost)| cost),
+        }
+    }
 }
 
-fn is_wall(pixel: Rgb<u8>) -> bool {
-    color_difference(pixel.0, [0, 0, 0]) < THRESHOLD
+fn luminance(p: [u8; 3]) -> f64 {
+    let [r, g, b] = p;
+    0.299 * r as f64 + 0.587 * g as f64 + 0.114 * b as f64
 }
 
 fn color_difference(p1: [u8; 3], p2: [u8; 3]) -> f64 {
@@ -75,18 +112,65 @@ fn color_difference(p1: [u8; 3], p2: [u8; 3]) -> f64 {
 mod tests {
     use super::*;
 
+    use crate::app::args::PaletteEntry;
+
     #[test]
     fn detect_red() {
+        let args = ImportArgs::default();
+        let reader = MazeImageReader { args: &args };
+
         let pixel = Rgb::from([237u8, 28, 36]);
-        assert!(is_start(pixel));
+        assert!(reader.is_start(pixel));
 
         let pixel = Rgb::from([10u8, 28, 36]);
-        assert!(!is_start(pixel));
+        assert!(!reader.is_start(pixel));
     }
 
     #[test]
     fn detect_green() {
+        let args = ImportArgs::default();
+        let reader = MazeImageReader { args: &args };
+
         let pixel = Rgb::from([34u8, 177, 76]);
-        assert!(is_goal(pixel));
+        assert!(reader.is_goal(pixel));
+    }
+
+    #[test]
+    fn luminance_cost() {
+        let args = ImportArgs {
+            mode: ImportMode::Luminance,
+            max_cost: 5.0,
+            ..Default::default()
+        };
+        let reader = MazeImageReader { args: &args };
+
+        assert!(!reader.is_wall(Rgb::from([0u8, 0, 0])));
+        assert_eq!(reader.cost(Rgb::from([255u8, 255, 255])), 1.0);
+        assert!((reader.cost(Rgb::from([0u8, 0, 0])) - 5.0).abs() < 1e-9);
+        assert!((reader.cost(Rgb::from([128u8, 128, 128])) - 2.992).abs() < 1e-2);
+    }
+
+    #[test]
+    fn palette_cost() {
+        let args = ImportArgs {
+            mode: ImportMode::Palette,
+            threshold: 50.0,
+            palette: vec![
+                PaletteEntry {
+                    color: Color([120, 70, 20]),
+                    cost: 4.0,
+                },
+                PaletteEntry {
+                    color: Color([0, 0, 255]),
+                    cost: 8.0,
+                },
+            ],
+            ..Default::default()
+        };
+        let reader = MazeImageReader { args: &args };
+
+        assert_eq!(reader.cost(Rgb::from([125u8, 72, 20])), 4.0);
+        assert_eq!(reader.cost(Rgb::from([0u8, 10, 250])), 8.0);
+        assert_eq!(reader.cost(Rgb::from([255u8, 255, 255])), 1.0);
     }
 }
@@ -9,6 +9,8 @@ use binary_reader::MazeBinaryReader;
 use image_reader::MazeImageReader;
 use maze_exit_lib::maze::Maze;
 
+use crate::app::args::ImportArgs;
+
 mod binary_reader;
 mod image_reader;
 
@@ -19,11 +21,11 @@ trait MazeReader {
     fn read_maze(&self, reader: impl Read + Seek) -> Result<Maze>;
 }
 
-pub fn read_maze<P: AsRef<Path>>(path: P) -> Result<Maze> {
+pub fn read_maze<P: AsRef<Path>>(path: P, import: &ImportArgs) -> Result<Maze> {
     let mut reader = File::open(path)?;
     if MazeBinaryReader.check(&mut reader)? {
         MazeBinaryReader.read_maze(reader)
     } else {
-        MazeImageReader.read_maze(reader)
+        MazeImageReader { args: import }.read_maze(reader)
     }
 }
@@ -1,6 +1,9 @@
 use anyhow::Result;
 use byteorder::{BigEndian, WriteBytesExt};
-use maze_exit_lib::{maze::Maze, position::Position};
+use maze_exit_lib::{
+    maze::Maze,
+    position::{PosFunctions, Position},
+};
 use std::io::Write;
 use zstd::Encoder;
 
@@ -41,6 +44,16 @@ impl<W: Write> MazeWriter<W> for BinaryMazeWriter {
 
         encoder.write_all(&maze_data)?;
 
+        // the multiplier of every cell follows, if any cell has one
+        if !maze.has_uniform_costs() {
+            for y in 0..maze.height() {
+                for x in 0..maze.width() {
+                    let pos = Position::try_convert(x, y)?;
+                    encoder.write_f32::<BigEndian>(maze.cost(pos) as f32)?;
+                }
+            }
+        }
+
         Ok(())
     }
 }
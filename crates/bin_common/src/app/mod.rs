@@ -1,3 +1,4 @@
 pub mod args;
 pub mod maze_readers;
 pub mod maze_writers;
+pub mod precomputed;
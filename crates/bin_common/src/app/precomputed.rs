@@ -0,0 +1,473 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{Result, anyhow, bail};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use maze_exit_lib::{
+    algorithm::Abstraction,
+    generator::{DiagonalMovement, JumpTable},
+    heuristics::{LandmarkSelection, Landmarks},
+    maze::Maze,
+    position::Position,
+};
+use zstd::{Decoder, Encoder};
+
+use crate::app::args::{Algorithm, Generator, Heuristic, SearchArgs};
+
+pub const JUMP_TABLE_HEADER: &str = "JPS2";
+pub const JUMP_TABLE_EXT: &str = "jps";
+pub const ABSTRACTION_HEADER: &str = "HPA2";
+pub const ABSTRACTION_EXT: &str = "hpa";
+pub const LANDMARKS_HEADER: &str = "ALT4";
+pub const LANDMARKS_EXT: &str = "alt";
+
+/// Data computed once per maze and reused by the searches that need it.
+#[derive(Default)]
+pub struct Precomputed {
+    pub jump_table: Option<JumpTable>,
+    pub abstraction: Option<Abstraction>,
+    pub landmarks: Option<Arc<Landmarks>>,
+}
+
+/// Loads the data needed by `search`: the one saved next to `maze_path` if it was built for
+/// `maze` with the same settings, or new data otherwise.
+pub fn load_precomputed<P: AsRef<Path>>(
+    maze_path: P,
+    maze: &Maze,
+    search: &SearchArgs,
+) -> Result<Precomputed> {
+    let maze_path = maze_path.as_ref();
+    let movement = search.diagonal_movement();
+    let mut precomputed = Precomputed::default();
+
+    if search.generator == Generator::JpsPlus {
+        let saved = read_if_exists(jump_table_path(maze_path), |f| read_jump_table(f, maze));
+        precomputed.jump_table = Some(
+            saved
+                .filter(|t| t.movement() == movement)
+                .unwrap_or_else(|| JumpTable::new(maze, movement)),
+        );
+    }
+
+    if search.algorithm == Algorithm::HpaStar {
+        let saved = read_if_exists(abstraction_path(maze_path), read_abstraction);
+        precomputed.abstraction = Some(
+            saved
+                .filter(|a| {
+                    a.movement() == movement
+                        && a.cluster_size() == search.cluster_size
+                        && a.matches(maze)
+                })
+                .unwrap_or_else(|| Abstraction::new(maze, search.cluster_size, movement)),
+        );
+    }
+
+    if search.heuristic == Heuristic::Landmarks {
+        let count = search.landmarks;
+        let selection = search.landmark_selection.into();
+        let saved = read_if_exists(landmarks_path(maze_path), |f| {
+            read_landmarks(f, maze, count)
+        });
+        precomputed.landmarks = Some(Arc::new(
+            saved
+                .filter(|l| l.movement() == movement && l.selection() == selection)
+                .unwrap_or_else(|| Landmarks::new(maze, count, selection, movement)),
+        ));
+    }
+
+    Ok(precomputed)
+}
+
+/// The data saved at `path`, if any. Data that cannot be read, corrupt or saved by an older
+/// version, is ignored so that it is computed again.
+fn read_if_exists<T>(path: PathBuf, read: impl FnOnce(File) -> Result<T>) -> Option<T> {
+    let file = File::open(&path).ok()?;
+    match read(file) {
+        Ok(data) => Some(data),
+        Err(e) => {
+            eprintln!("Warning: ignoring {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// Where the jump table of the maze at `maze_path` is saved.
+pub fn jump_table_path<P: AsRef<Path>>(maze_path: P) -> PathBuf {
+    maze_path.as_ref().with_extension(JUMP_TABLE_EXT)
+}
+
+/// Where the HPA* abstraction of the maze at `maze_path` is saved.
+pub fn abstraction_path<P: AsRef<Path>>(maze_path: P) -> PathBuf {
+    maze_path.as_ref().with_extension(ABSTRACTION_EXT)
+}
+
+/// Where the ALT landmarks of the maze at `maze_path` are saved.
+pub fn landmarks_path<P: AsRef<Path>>(maze_path: P) -> PathBuf {
+    maze_path.as_ref().with_extension(LANDMARKS_EXT)
+}
+
+pub fn write_jump_table(table: &JumpTable, mut writer: impl Write) -> Result<()> {
+    write!(writer, "{}", JUMP_TABLE_HEADER)?;
+
+    let mut encoder = BufWriter::new(Encoder::new(writer, 0)?.auto_finish());
+
+    encoder.write_u32::<BigEndian>(table.width())?;
+    encoder.write_u32::<BigEndian>(table.height())?;
+    encoder.write_u8(movement_to_u8(table.movement()))?;
+    encoder.write_u64::<BigEndian>(table.fingerprint())?;
+    for &d in table.distances() {
+        encoder.write_i16::<BigEndian>(d)?;
+    }
+    encoder.flush()?;
+
+    Ok(())
+}
+
+/// Reads a jump table saved for `maze`, failing on one saved for another maze before
+/// reading its distances.
+pub fn read_jump_table(mut reader: impl Read, maze: &Maze) -> Result<JumpTable> {
+    check_header(&mut reader, JUMP_TABLE_HEADER)?;
+
+    let mut decoder = BufReader::new(Decoder::new(reader)?);
+
+    let w = decoder.read_u32::<BigEndian>()?;
+    let h = decoder.read_u32::<BigEndian>()?;
+    let movement = movement_from_u8(decoder.read_u8()?)?;
+    let fingerprint = decoder.read_u64::<BigEndian>()?;
+    check_maze(maze, w, h, fingerprint)?;
+    let mut distances = vec![0; w as usize * h as usize * 8];
+    decoder.read_i16_into::<BigEndian>(&mut distances)?;
+
+    JumpTable::from_parts(w, h, movement, fingerprint, distances).map_err(|e| anyhow!(e))
+}
+
+pub fn write_abstraction(abstraction: &Abstraction, mut writer: impl Write) -> Result<()> {
+    write!(writer, "{}", ABSTRACTION_HEADER)?;
+
+    let mut encoder = BufWriter::new(Encoder::new(writer, 0)?.auto_finish());
+
+    encoder.write_u32::<BigEndian>(abstraction.width())?;
+    encoder.write_u32::<BigEndian>(abstraction.height())?;
+    encoder.write_u32::<BigEndian>(abstraction.cluster_size())?;
+    encoder.write_u8(movement_to_u8(abstraction.movement()))?;
+    encoder.write_u64::<BigEndian>(abstraction.fingerprint())?;
+
+    encoder.write_u32::<BigEndian>(abstraction.nodes().len() as u32)?;
+    for node in abstraction.nodes() {
+        encoder.write_i32::<BigEndian>(node.x)?;
+        encoder.write_i32::<BigEndian>(node.y)?;
+    }
+    encoder.write_u32::<BigEndian>(abstraction.edges().len() as u32)?;
+    for &(from, to, cost) in abstraction.edges() {
+        encoder.write_u32::<BigEndian>(from)?;
+        encoder.write_u32::<BigEndian>(to)?;
+        encoder.write_u64::<BigEndian>(cost)?;
+    }
+    encoder.flush()?;
+
+    Ok(())
+}
+
+pub fn read_abstraction(mut reader: impl Read) -> Result<Abstraction> {
+    check_header(&mut reader, ABSTRACTION_HEADER)?;
+
+    let mut decoder = BufReader::new(Decoder::new(reader)?);
+
+    let w = decoder.read_u32::<BigEndian>()?;
+    let h = decoder.read_u32::<BigEndian>()?;
+    let cluster_size = decoder.read_u32::<BigEndian>()?;
+    let movement = movement_from_u8(decoder.read_u8()?)?;
+    let fingerprint = decoder.read_u64::<BigEndian>()?;
+
+    let nodes = (0..decoder.read_u32::<BigEndian>()?)
+        .map(|_| {
+            let x = decoder.read_i32::<BigEndian>()?;
+            let y = decoder.read_i32::<BigEndian>()?;
+            Ok(Position::new(x, y))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let edges = (0..decoder.read_u32::<BigEndian>()?)
+        .map(|_| {
+            let from = decoder.read_u32::<BigEndian>()?;
+            let to = decoder.read_u32::<BigEndian>()?;
+            let cost = decoder.read_u64::<BigEndian>()?;
+            Ok((from, to, cost))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Abstraction::from_parts(w, h, cluster_size, movement, fingerprint, nodes, edges)
+        .map_err(|e| anyhow!(e))
+}
+
+pub fn write_landmarks(landmarks: &Landmarks, mut writer: impl Write) -> Result<()> {
+    write!(writer, "{}", LANDMARKS_HEADER)?;
+
+    let mut encoder = BufWriter::new(Encoder::new(writer, 0)?.auto_finish());
+
+    encoder.write_u32::<BigEndian>(landmarks.width())?;
+    encoder.write_u32::<BigEndian>(landmarks.height())?;
+    encoder.write_u8(movement_to_u8(landmarks.movement()))?;
+    encoder.write_u8(selection_to_u8(landmarks.selection()))?;
+    encoder.write_u32::<BigEndian>(landmarks.count() as u32)?;
+    encoder.write_u64::<BigEndian>(landmarks.fingerprint())?;
+
+    encoder.write_u32::<BigEndian>(landmarks.landmarks().len() as u32)?;
+    for landmark in landmarks.landmarks() {
+        encoder.write_i32::<BigEndian>(landmark.x)?;
+        encoder.write_i32::<BigEndian>(landmark.y)?;
+    }
+    for &d in landmarks.distances() {
+        encoder.write_u64::<BigEndian>(d)?;
+    }
+    encoder.flush()?;
+
+    Ok(())
+}
+
+/// Reads `count` landmarks saved for `maze`, failing on others before reading their
+/// distances.
+pub fn read_landmarks(mut reader: impl Read, maze: &Maze, count: usize) -> Result<Landmarks> {
+    check_header(&mut reader, LANDMARKS_HEADER)?;
+
+    let mut decoder = BufReader::new(Decoder::new(reader)?);
+
+    let w = decoder.read_u32::<BigEndian>()?;
+    let h = decoder.read_u32::<BigEndian>()?;
+    let movement = movement_from_u8(decoder.read_u8()?)?;
+    let selection = selection_from_u8(decoder.read_u8()?)?;
+    let saved_count = decoder.read_u32::<BigEndian>()? as usize;
+    let fingerprint = decoder.read_u64::<BigEndian>()?;
+    check_maze(maze, w, h, fingerprint)?;
+    if saved_count != count {
+        bail!("Saved {saved_count} landmarks, not {count}");
+    }
+
+    let len = decoder.read_u32::<BigEndian>()? as usize;
+    let distance_count = (len <= count)
+        .then(|| len.checked_mul(w as usize)?.checked_mul(h as usize))
+        .flatten()
+        .ok_or_else(|| anyhow!("Invalid landmarks: {len} saved, at most {count} asked for"))?;
+    let landmarks = (0..len)
+        .map(|_| {
+            let x = decoder.read_i32::<BigEndian>()?;
+            let y = decoder.read_i32::<BigEndian>()?;
+            Ok(Position::new(x, y))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let mut distances = vec![0; distance_count];
+    decoder.read_u64_into::<BigEndian>(&mut distances)?;
+
+    Landmarks::from_parts(
+        w,
+        h,
+        movement,
+        selection,
+        count,
+        fingerprint,
+        landmarks,
+        distances,
+    )
+    .map_err(|e| anyhow!(e))
+}
+
+/// Fails unless the saved dimensions and fingerprint are the ones of `maze`.
+fn check_maze(maze: &Maze, width: u32, height: u32, fingerprint: u64) -> Result<()> {
+    if (width, height) != (maze.width(), maze.height()) || fingerprint != maze.fingerprint() {
+        bail!("Saved for another maze, {width}x{height}");
+    }
+    Ok(())
+}
+
+fn check_header(reader: &mut impl Read, header: &str) -> Result<()> {
+    let mut buf = vec![0; header.len()];
+    reader.read_exact(&mut buf)?;
+    if buf != header.as_bytes() {
+        bail!("Invalid header, expected {header}");
+    }
+    Ok(())
+}
+
+fn movement_to_u8(movement: DiagonalMovement) -> u8 {
+    match movement {
+        DiagonalMovement::Always => 0,
+        DiagonalMovement::Never => 1,
+        DiagonalMovement::IfNoObstacles => 2,
+        DiagonalMovement::IfAtMostOneObstacle => 3,
+    }
+}
+
+fn movement_from_u8(value: u8) -> Result<DiagonalMovement> {
+    Ok(match value {
+        0 => DiagonalMovement::Always,
+        1 => DiagonalMovement::Never,
+        2 => DiagonalMovement::IfNoObstacles,
+        3 => DiagonalMovement::IfAtMostOneObstacle,
+        _ => bail!("Invalid diagonal movement {value}"),
+    })
+}
+
+fn selection_to_u8(selection: LandmarkSelection) -> u8 {
+    match selection {
+        LandmarkSelection::Farthest => 0,
+        LandmarkSelection::Corners => 1,
+    }
+}
+
+fn selection_from_u8(value: u8) -> Result<LandmarkSelection> {
+    Ok(match value {
+        0 => LandmarkSelection::Farthest,
+        1 => LandmarkSelection::Corners,
+        _ => bail!("Invalid landmark selection {value}"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Seek};
+
+    use super::*;
+
+    fn maze() -> Maze {
+        let mut maze = Maze::new(70, 5, Position::new(0, 0), Position::new(69, 4));
+        maze.set(Position::new(35, 1), true).unwrap();
+        maze.set(Position::new(35, 2), true).unwrap();
+        maze
+    }
+
+    #[test]
+    fn write_and_read_jump_table() {
+        let mut maze = maze();
+        let table = JumpTable::new(&maze, DiagonalMovement::IfNoObstacles);
+
+        let mut buf = Cursor::new(Vec::new());
+        write_jump_table(&table, &mut buf).unwrap();
+        buf.rewind().unwrap();
+        let read = read_jump_table(&mut buf, &maze).unwrap();
+
+        assert_eq!(read, table);
+        assert!(read.matches(&maze));
+
+        maze.set(Position::new(35, 3), true).unwrap();
+        assert!(!read.matches(&maze));
+        buf.rewind().unwrap();
+        assert!(read_jump_table(buf, &maze).is_err());
+    }
+
+    #[test]
+    fn reject_jump_table_of_another_maze() {
+        // a corrupt size must not be allocated before it is checked
+        let mut buf = Cursor::new(Vec::new());
+        write!(buf, "{}", JUMP_TABLE_HEADER).unwrap();
+        let mut encoder = Encoder::new(&mut buf, 0).unwrap().auto_finish();
+        encoder.write_u32::<BigEndian>(u32::MAX).unwrap();
+        encoder.write_u32::<BigEndian>(u32::MAX).unwrap();
+        encoder.write_u8(0).unwrap();
+        encoder.write_u64::<BigEndian>(0).unwrap();
+        drop(encoder);
+
+        buf.rewind().unwrap();
+        assert!(read_jump_table(buf, &maze()).is_err());
+    }
+
+    #[test]
+    fn write_and_read_abstraction() {
+        let maze = maze();
+        let abstraction = Abstraction::new(&maze, 16, DiagonalMovement::Never);
+
+        let mut buf = Cursor::new(Vec::new());
+        write_abstraction(&abstraction, &mut buf).unwrap();
+        buf.rewind().unwrap();
+        let read = read_abstraction(&mut buf).unwrap();
+
+        assert!(read.matches(&maze));
+        assert_eq!(read.cluster_size(), 16);
+        assert_eq!(read.movement(), DiagonalMovement::Never);
+        assert_eq!(read.nodes(), abstraction.nodes());
+        assert_eq!(read.edges(), abstraction.edges());
+        assert!(read_jump_table(Cursor::new(buf.into_inner()), &maze).is_err());
+    }
+
+    #[test]
+    fn write_and_read_landmarks() {
+        let mut maze = maze();
+        let landmarks = Landmarks::new(
+            &maze,
+            3,
+            LandmarkSelection::Corners,
+            DiagonalMovement::Always,
+        );
+
+        let mut buf = Cursor::new(Vec::new());
+        write_landmarks(&landmarks, &mut buf).unwrap();
+        buf.rewind().unwrap();
+        let read = read_landmarks(&mut buf, &maze, 3).unwrap();
+
+        assert_eq!(read, landmarks);
+        assert!(read.matches(&maze));
+        buf.rewind().unwrap();
+        assert!(read_landmarks(&mut buf, &maze, 4).is_err());
+
+        maze.set_cost(Position::new(10, 2), 2.0).unwrap();
+        assert!(!read.matches(&maze));
+    }
+
+    #[test]
+    fn write_and_read_fewer_landmarks() {
+        let maze = Maze::new(3, 1, Position::new(0, 0), Position::new(2, 0));
+        let landmarks = Landmarks::new(
+            &maze,
+            5,
+            LandmarkSelection::Farthest,
+            DiagonalMovement::Always,
+        );
+        assert!(landmarks.landmarks().len() < 5);
+
+        let mut buf = Cursor::new(Vec::new());
+        write_landmarks(&landmarks, &mut buf).unwrap();
+        buf.rewind().unwrap();
+        let read = read_landmarks(buf, &maze, 5).unwrap();
+
+        assert_eq!(read.count(), 5);
+        assert_eq!(read, landmarks);
+    }
+
+    #[test]
+    fn ignore_unreadable_data() {
+        let path = std::env::temp_dir().join("maze_exit_corrupt.alt");
+        std::fs::write(&path, b"ALT2 not landmarks").unwrap();
+        let read = |f| read_landmarks(f, &maze(), 8);
+        assert!(read_if_exists(path.clone(), read).is_none());
+        std::fs::remove_file(&path).unwrap();
+        assert!(read_if_exists(path, read).is_none());
+    }
+
+    #[test]
+    fn reject_landmarks_of_another_maze() {
+        // corrupt sizes must not be allocated before they are checked
+        let header = |w: u32, h: u32, count: u32, len: u32, fingerprint: u64| {
+            let mut buf = Cursor::new(Vec::new());
+            write!(buf, "{}", LANDMARKS_HEADER).unwrap();
+            let mut encoder = Encoder::new(&mut buf, 0).unwrap().auto_finish();
+            encoder.write_u32::<BigEndian>(w).unwrap();
+            encoder.write_u32::<BigEndian>(h).unwrap();
+            encoder.write_u8(0).unwrap();
+            encoder.write_u8(0).unwrap();
+            encoder.write_u32::<BigEndian>(count).unwrap();
+            encoder.write_u64::<BigEndian>(fingerprint).unwrap();
+            encoder.write_u32::<BigEndian>(len).unwrap();
+            drop(encoder);
+            buf.rewind().unwrap();
+            buf
+        };
+
+        let maze = maze();
+        let (w, h, fingerprint) = (maze.width(), maze.height(), maze.fingerprint());
+        assert!(read_landmarks(header(u32::MAX, u32::MAX, 8, 8, 0), &maze, 8).is_err());
+        assert!(read_landmarks(header(w, h, u32::MAX, u32::MAX, fingerprint), &maze, 8).is_err());
+        assert!(read_landmarks(header(w, h, 8, u32::MAX, fingerprint), &maze, 8).is_err());
+    }
+}
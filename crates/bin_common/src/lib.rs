@@ -3,37 +3,157 @@ mod display;
 
 use std::time::Instant;
 
-use anyhow::Result;
+use anyhow::{Result, anyhow, bail};
 use maze_exit_lib::{
-    algorithm::{Info, Message, a_star},
+    algorithm::{
+        AraStar, BestFirst, BidirectionalAStar, BreadthFirst, HpaStar, IdaStar, Info,
+        LazyThetaStar, Message, SearchAlgorithm, SearchLimits, SmaStar, StopReason, ThetaStar,
+    },
     channel::ChannelSender,
-    generator::JpsGenerator,
-    heuristics::MazeHeuristic,
+    cost::to_steps,
+    generator::{
+        BitJpsGenerator, ChildrenGenerator, DiagonalMovement, EightNeighborGenerator,
+        FourNeighborGenerator, Jps4Generator, JpsGenerator, JpsPlusGenerator,
+    },
+    heuristics::{
+        ChebyshevHeuristic, DiagonalHeuristic, EuclideanHeuristic, LandmarkHeuristic,
+        ManhattanHeuristic, MazeHeuristic, OctileHeuristic, WeightedHeuristic,
+    },
     maze::Maze,
+    subgoal::SubgoalGraph,
 };
 
-pub use app::args::{Args, parse_args};
+pub use app::args::{
+    Algorithm, Args, Color, Diagonal, Generator, Heuristic, ImportArgs, ImportMode, PaletteEntry,
+    Queue, SearchArgs, Selection, TieOrder, parse_args,
+};
 pub use app::maze_readers::{BinaryReaderCell, MAZE_BINARY_READER_HEADER, read_maze};
 pub use app::maze_writers::{
     MazeWriter, MazeWriterWithPath, binary_writer::BinaryMazeWriter, image_writer::ImageMazeWriter,
 };
+pub use app::precomputed::{
+    ABSTRACTION_EXT, JUMP_TABLE_EXT, LANDMARKS_EXT, Precomputed, abstraction_path, jump_table_path,
+    landmarks_path, load_precomputed, read_abstraction, read_jump_table, read_landmarks,
+    write_abstraction, write_jump_table, write_landmarks,
+};
 pub use display::Displayer;
 
+/// The heuristic chosen by `search`, reading the landmarks of ALT from `precomputed`.
+pub fn build_heuristic(
+    maze: &Maze,
+    search: &SearchArgs,
+    precomputed: &Precomputed,
+) -> Result<Box<dyn MazeHeuristic + Send>> {
+    let movement = search.diagonal_movement();
+    let heuristic: Box<dyn MazeHeuristic + Send> = match search.heuristic {
+        Heuristic::Diagonal => Box::new(
+            DiagonalHeuristic::with_movement(maze, movement)
+                .with_tie_break(search.heuristic_factor),
+        ),
+        Heuristic::Octile => Box::new(OctileHeuristic::with_movement(maze, movement)),
+        Heuristic::Manhattan => Box::new(ManhattanHeuristic::new(maze)),
+        Heuristic::Euclidean => Box::new(EuclideanHeuristic::new(maze)),
+        Heuristic::Chebyshev => Box::new(ChebyshevHeuristic::new(maze)),
+        Heuristic::Landmarks => {
+            let landmarks =
+                (precomputed.landmarks.clone()).ok_or_else(|| anyhow!("ALT needs landmarks"))?;
+            Box::new(LandmarkHeuristic::new(maze, landmarks))
+        }
+    };
+
+    Ok(if search.heuristic_weight == 1.0 {
+        heuristic
+    } else {
+        Box::new(WeightedHeuristic::new(heuristic, search.heuristic_weight))
+    })
+}
+
+/// The children generator chosen by `search`, reading the jump table of JPS+ from
+/// `precomputed`.
+pub fn build_generator<'a>(
+    maze: &'a Maze,
+    search: &SearchArgs,
+    precomputed: &'a Precomputed,
+) -> Result<Box<dyn ChildrenGenerator + 'a>> {
+    let movement = search.diagonal_movement();
+    Ok(match search.generator {
+        Generator::Jps => Box::new(JpsGenerator::with_movement(maze, movement)),
+        Generator::BitJps => Box::new(BitJpsGenerator::with_movement(maze, movement)),
+        Generator::JpsPlus => {
+            let table = (precomputed.jump_table.as_ref())
+                .ok_or_else(|| anyhow!("JPS+ needs a jump table"))?;
+            Box::new(JpsPlusGenerator::new(maze, table, movement).map_err(|e| anyhow!(e))?)
+        }
+        Generator::Jps4 => Box::new(Jps4Generator::new(maze)),
+        Generator::Four => Box::new(FourNeighborGenerator::new(maze)),
+        Generator::Eight => Box::new(EightNeighborGenerator::with_movement(maze, movement)),
+    })
+}
+
 pub fn find_path(
     maze: &Maze,
     heuristic: Box<dyn MazeHeuristic>,
+    search: &SearchArgs,
+    precomputed: &Precomputed,
+    limits: SearchLimits,
     channel: impl ChannelSender<Message>,
 ) -> Result<()> {
-    let generator = JpsGenerator::new(maze);
+    let movement = search.diagonal_movement();
+    let generator = build_generator(maze, search, precomputed)?;
+    let generator = generator.as_ref();
+    let heuristic = heuristic.as_ref();
+
+    let subgoals = if search.algorithm == Algorithm::Subgoal {
+        if movement != DiagonalMovement::IfNoObstacles {
+            bail!("Subgoal graphs need --diagonal if-no-obstacles");
+        }
+        Some(SubgoalGraph::new(maze).map_err(|e| anyhow!(e))?)
+    } else {
+        None
+    };
+
     let start_time = Instant::now();
 
-    let mut info = a_star(
-        maze.start(),
-        maze.goal(),
-        heuristic.as_ref(),
-        &generator,
-        channel.clone(),
-    );
+    macro_rules! run {
+        ($algorithm: expr) => {
+            $algorithm.search_with_limits(
+                maze.start(),
+                maze.goal(),
+                heuristic,
+                generator,
+                channel.clone(),
+                &limits,
+            )
+        };
+    }
+
+    let queue = search.queue.into();
+    let best_first = |depth_weight, heuristic_weight| {
+        BestFirst::new(depth_weight, heuristic_weight)
+            .with_tie_break(search.tie_order.into())
+            .with_queue(queue)
+    };
+
+    let mut info = match search.algorithm {
+        Algorithm::AStar => run!(best_first(1.0, 1.0)),
+        Algorithm::WeightedAStar => run!(best_first(1.0, search.epsilon)),
+        Algorithm::Dijkstra => run!(best_first(1.0, 0.0)),
+        Algorithm::Bfs => run!(BreadthFirst),
+        Algorithm::Greedy => run!(best_first(0.0, 1.0)),
+        Algorithm::Bidirectional => run!(BidirectionalAStar),
+        Algorithm::IdaStar => run!(IdaStar),
+        Algorithm::SmaStar => run!(SmaStar::new(search.max_nodes)),
+        Algorithm::AraStar => run!(AraStar::new(search.epsilon, search.epsilon_step)),
+        Algorithm::ThetaStar => run!(ThetaStar::new(maze, movement, search.densify)),
+        Algorithm::LazyThetaStar => run!(LazyThetaStar::new(maze, movement, search.densify)),
+        Algorithm::HpaStar => {
+            let abstraction = (precomputed.abstraction.as_ref())
+                .ok_or_else(|| anyhow!("HPA* needs an abstraction"))?;
+            let hpa = HpaStar::new(maze, abstraction).map_err(|e| anyhow!(e))?;
+            run!(hpa.with_queue(queue))
+        }
+        Algorithm::Subgoal => run!(subgoals.as_ref().expect("subgoal graph built above")),
+    };
 
     let end_time = Instant::now() - start_time;
     info.time = end_time;
@@ -49,14 +169,114 @@ pub fn print_info(info: &Info) {
             let path_len = path.path_len();
             println!("Path found!");
             println!("Length: {}", path_len);
-            println!("Cost: {}", path.cost);
+            println!("Cost: {}", to_steps(path.cost));
         }
         None => {
             println!("Path not found");
         }
     };
+    if !matches!(info.stop, StopReason::Found | StopReason::Exhausted) {
+        println!("Stopped: {:?}", info.stop);
+    }
 
     println!("Time: {}s", info.time.as_secs_f64());
     println!("Max queue length: {}", info.max_length);
     println!("Nodes visited: {}", info.nodes);
+    if info.abstract_nodes > 0 {
+        println!("Abstract nodes visited: {}", info.abstract_nodes);
+        println!("Refined nodes visited: {}", info.refined_nodes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use clap::Parser;
+    use maze_exit_lib::{
+        algorithm::a_star,
+        channel::{channel, noop_sender},
+        cost::Cost,
+        heuristics::DiagonalHeuristic,
+    };
+
+    use super::*;
+
+    fn sample_mazes() -> impl Iterator<Item = (PathBuf, Maze)> {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../img");
+        dir.read_dir().unwrap().map(|entry| {
+            let path = entry.unwrap().path();
+            let maze = read_maze(&path, &ImportArgs::default()).unwrap();
+            (path, maze)
+        })
+    }
+
+    /// Cost of the path found on `maze` with the command line arguments `args`.
+    fn cli_cost(maze: &Maze, args: &[&str]) -> Option<Cost> {
+        let args = Args::parse_from(["maze_exit", "maze.png"].iter().chain(args));
+        let precomputed = Precomputed::default();
+        let heuristic = build_heuristic(maze, &args.search, &precomputed).unwrap();
+
+        let (sender, receiver) = channel();
+        find_path(
+            maze,
+            heuristic,
+            &args.search,
+            &precomputed,
+            SearchLimits::default(),
+            sender,
+        )
+        .unwrap();
+        let info = receiver
+            .iter()
+            .find_map(|message| match message {
+                Message::End(info) => Some(info),
+                _ => None,
+            })
+            .unwrap();
+        info.path.map(|p| p.cost)
+    }
+
+    #[test]
+    fn bit_jps_matches_jps_on_sample_mazes() {
+        for (path, maze) in sample_mazes() {
+            let heuristic = DiagonalHeuristic::new(&maze);
+
+            let search = |generator: &dyn ChildrenGenerator| {
+                a_star(
+                    maze.start(),
+                    maze.goal(),
+                    &heuristic,
+                    generator,
+                    noop_sender(),
+                )
+            };
+            let jps = search(&JpsGenerator::new(&maze));
+            let bit_jps = search(&BitJpsGenerator::new(&maze));
+
+            assert_eq!(bit_jps.nodes, jps.nodes, "{path:?}");
+            assert_eq!(
+                bit_jps.path.map(|p| p.path),
+                jps.path.map(|p| p.path),
+                "{path:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn subgoal_matches_dijkstra_with_default_arguments() {
+        // the larger mazes take too long to solve with Dijkstra in debug builds
+        let mazes = sample_mazes().filter(|(_, m)| m.width() * m.height() < 200_000);
+        for (path, maze) in mazes {
+            if !maze.has_uniform_costs() {
+                continue;
+            }
+            let diagonal = ["--diagonal", "if-no-obstacles"];
+            assert_eq!(
+                cli_cost(&maze, &[&["-a", "subgoal"][..], &diagonal].concat()),
+                cli_cost(&maze, &[&["-a", "dijkstra"][..], &diagonal].concat()),
+                "{path:?}"
+            );
+        }
+    }
 }
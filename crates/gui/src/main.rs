@@ -1,27 +1,30 @@
 use std::{
     fs::File,
+    mem,
     sync::{Arc, mpsc},
-    thread,
+    thread::{self, JoinHandle},
 };
 
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use clap::Parser;
 use macroquad::{
-    camera::{Camera2D, set_camera},
+    camera::{Camera2D, set_camera, set_default_camera},
     color::{BLACK, Color, WHITE},
     input::{KeyCode, get_keys_pressed, mouse_wheel},
     math::{Rect, Vec2},
     shapes::draw_rectangle,
+    text::draw_text,
     window::{Conf, clear_background, next_frame},
 };
 use maze_exit_bin_common::{
-    Args, ImageMazeWriter, MazeWriter, MazeWriterWithPath, find_path, parse_args, print_info,
-    read_maze,
+    Args, ImageMazeWriter, MazeWriter, MazeWriterWithPath, Precomputed, SearchArgs,
+    build_heuristic, find_path, load_precomputed, parse_args, print_info, read_maze,
 };
 use maze_exit_lib::{
-    algorithm::Message,
+    algorithm::{Frontier, Message, SearchLimits},
     channel::{ChannelSender, channel, sync_channel},
-    heuristics::{DiagonalHeuristic, MazeHeuristic},
+    cost::{Cost, to_steps},
+    heuristics::MazeHeuristic,
     maze::Maze,
     position::Position,
 };
@@ -45,18 +48,23 @@ const BLUE: Color = Color::new(0.0, 0.0, 1.0, 1.0);
 
 struct App {
     maze: Arc<Maze>,
-    queue: Vec<(Position, f64)>,
+    queue: Vec<(Position, Cost)>,
+    backward_queue: Vec<(Position, Cost)>,
     path: Option<Vec<Position>>,
+    /// Cost of the last path improved by an anytime search, and its suboptimality bound.
+    improved: Option<(Cost, f64)>,
     end: bool,
     camera: Camera2D,
     original_zoom: Vec2,
     move_offset: f32,
     buffer_size: u8,
     solved: bool,
+    search: SearchArgs,
+    precomputed: Precomputed,
 }
 
 impl App {
-    fn new(maze: Maze, buffer_size: u8) -> Self {
+    fn new(maze: Maze, buffer_size: u8, search: SearchArgs, precomputed: Precomputed) -> Self {
         let camera = Camera2D::from_display_rect(Rect::new(
             0.0,
             0.0,
@@ -68,44 +76,65 @@ impl App {
         Self {
             maze: Arc::new(maze),
             queue: Vec::new(),
+            backward_queue: Vec::new(),
             path: None,
+            improved: None,
             end: false,
             camera,
             original_zoom,
             move_offset: 1.0,
             buffer_size,
             solved: false,
+            search,
+            precomputed,
         }
     }
 
-    async fn main(mut self) {
-        let heuristic = Box::new(DiagonalHeuristic::new(&self.maze));
+    async fn main(mut self) -> Result<()> {
+        let heuristic = build_heuristic(&self.maze, &self.search, &self.precomputed)?;
         let start_to_goal = heuristic.compute_heuristic(self.maze.start());
 
         let maze = self.maze.clone();
+        let search = self.search.clone();
+        let precomputed = mem::take(&mut self.precomputed);
 
         fn start(
             maze: Arc<Maze>,
             heuristic: Box<dyn MazeHeuristic + Send>,
+            search: SearchArgs,
+            precomputed: Precomputed,
             tx: impl ChannelSender<Message> + Send + 'static,
-        ) {
-            thread::spawn(move || find_path(&maze, heuristic, tx));
+        ) -> JoinHandle<Result<()>> {
+            thread::spawn(move || {
+                find_path(
+                    &maze,
+                    heuristic,
+                    &search,
+                    &precomputed,
+                    SearchLimits::default(),
+                    tx,
+                )
+            })
         }
 
-        let rx = match self.buffer_size {
+        let (rx, mut searching) = match self.buffer_size {
             0 => {
                 let (tx, rx) = channel();
-                start(maze, heuristic, tx);
-                rx
+                (rx, Some(start(maze, heuristic, search, precomputed, tx)))
             }
             n => {
                 let (tx, rx) = sync_channel(n as usize);
-                start(maze, heuristic, tx);
-                rx
+                (rx, Some(start(maze, heuristic, search, precomputed, tx)))
             }
         };
 
         while !self.end {
+            if let Some(handle) = searching.take_if(|h| h.is_finished()) {
+                handle
+                    .join()
+                    .map_err(|_| anyhow!("The search thread panicked"))??;
+            }
+
             clear_background(WHITE);
 
             self.handle_input();
@@ -116,8 +145,13 @@ impl App {
 
             self.draw_frame(&rx, start_to_goal);
 
+            set_default_camera();
+            self.draw_status();
+
             next_frame().await;
         }
+
+        Ok(())
     }
 
     fn handle_zoom(&mut self) {
@@ -150,10 +184,8 @@ impl App {
                 KeyCode::C => {
                     self.camera.offset = Vec2::ZERO;
                 }
-                KeyCode::S => {
-                    if self.solved {
-                        let _ = self.save_img();
-                    }
+                KeyCode::S if self.solved => {
+                    let _ = self.save_img();
                 }
                 _ => (),
             }
@@ -166,17 +198,22 @@ impl App {
         draw_rectangle(x, y, 1.0, 1.0, color);
     }
 
-    fn draw_frame(&mut self, rx: &mpsc::Receiver<Message>, start_to_goal: f64) {
+    fn draw_frame(&mut self, rx: &mpsc::Receiver<Message>, start_to_goal: Cost) {
         self.handle_messages(rx);
 
         self.draw_point(self.maze.start(), RED);
         self.draw_point(self.maze.goal(), GREEN);
 
         for (pos, dist) in self.queue.iter().copied() {
-            let ratio = (dist / start_to_goal) as f32;
+            let ratio = dist as f32 / start_to_goal as f32;
             self.draw_point(pos, Color::new(ratio, 1.0 - ratio, 0.0, 1.0));
         }
 
+        for (pos, dist) in self.backward_queue.iter().copied() {
+            let ratio = dist as f32 / start_to_goal as f32;
+            self.draw_point(pos, Color::new(0.0, 1.0 - ratio, ratio, 1.0));
+        }
+
         for pos in self.maze.walls() {
             self.draw_point(pos, BLACK);
         }
@@ -188,12 +225,27 @@ impl App {
         }
     }
 
+    fn draw_status(&self) {
+        if let Some((cost, bound)) = self.improved {
+            let status = format!("Cost: {} (bound {:.2})", to_steps(cost), bound);
+            draw_text(&status, 10.0, 30.0, 30.0, BLUE);
+        }
+    }
+
     fn handle_messages(&mut self, rx: &mpsc::Receiver<Message>) {
         while let Ok(msg) = rx.try_recv() {
             match msg {
-                Message::Enqueued(pos, dist) => {
+                Message::Enqueued(pos, dist)
+                | Message::FrontierEnqueued(Frontier::Forward, pos, dist) => {
                     self.queue.push((pos, dist));
                 }
+                Message::FrontierEnqueued(Frontier::Backward, pos, dist) => {
+                    self.backward_queue.push((pos, dist));
+                }
+                Message::Improved(path, bound) => {
+                    self.improved = Some((path.cost, bound));
+                    self.path.replace(path.path);
+                }
                 Message::End(info) => {
                     if !self.solved {
                         self.solved = true;
@@ -233,10 +285,12 @@ struct GuiArgs {
 #[macroquad::main(window_conf)]
 async fn main() -> Result<()> {
     let args: GuiArgs = parse_args();
-    let maze = read_maze(args.common.img_path)?;
+    let maze = read_maze(&args.common.img_path, &args.common.import)?;
+
+    let precomputed = load_precomputed(&args.common.img_path, &maze, &args.common.search)?;
 
-    let app = App::new(maze, args.buffer_size);
-    app.main().await;
+    let app = App::new(maze, args.buffer_size, args.common.search, precomputed);
+    app.main().await?;
 
     Ok(())
 }
@@ -1,8 +1,39 @@
-use std::thread;
+use std::{thread, time::Duration};
 
 use anyhow::{Result, bail};
-use maze_exit_bin_common::{Args, find_path, parse_args, print_info, read_maze};
-use maze_exit_lib::{algorithm::Message, channel::channel, heuristics::DiagonalHeuristic};
+use clap::{Parser, Subcommand};
+use maze_exit_bin_common::{
+    Args, build_generator, build_heuristic, find_path, load_precomputed, parse_args, print_info,
+    read_maze,
+};
+use maze_exit_lib::{
+    algorithm::{Message, SearchLimits},
+    channel::channel,
+    cost::to_steps,
+    heuristics::check_heuristic,
+};
+
+#[derive(Parser, Debug)]
+struct HeadlessArgs {
+    #[command(flatten)]
+    common: Args,
+    /// Give up the search after this many seconds
+    #[arg(long)]
+    timeout: Option<f64>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Check that the heuristic never overestimates the cost to the goal and is consistent,
+    /// instead of searching
+    CheckHeuristic {
+        /// Overestimates and inconsistencies listed, at most
+        #[arg(long, default_value_t = 10)]
+        show: usize,
+    },
+}
 
 fn main() -> Result<()> {
     #[cfg(feature = "debug-so")]
@@ -10,10 +41,39 @@ fn main() -> Result<()> {
         backtrace_on_stack_overflow::enable()
     };
 
-    let args: Args = parse_args();
+    let HeadlessArgs {
+        common: args,
+        timeout,
+        command,
+    } = parse_args();
 
-    let maze = read_maze(&args.img_path)?;
-    let heuristic = Box::new(DiagonalHeuristic::new(&maze));
+    let maze = read_maze(&args.img_path, &args.import)?;
+    let precomputed = load_precomputed(&args.img_path, &maze, &args.search)?;
+    let heuristic = build_heuristic(&maze, &args.search, &precomputed)?;
+
+    if let Some(Command::CheckHeuristic { show }) = command {
+        let generator = build_generator(&maze, &args.search, &precomputed)?;
+        let report = check_heuristic(&maze, generator.as_ref(), heuristic.as_ref());
+        for o in report.overestimates.iter().take(show) {
+            println!(
+                "Overestimate at {}: {} > {}",
+                o.cell,
+                to_steps(o.heuristic),
+                to_steps(o.cost)
+            );
+        }
+        for i in report.inconsistencies.iter().take(show) {
+            println!(
+                "Inconsistent from {} to {}: drop of {} > {}",
+                i.from,
+                i.to,
+                to_steps(i.drop),
+                to_steps(i.weight)
+            );
+        }
+        println!("{report}");
+        return Ok(());
+    }
 
     let (tx, rx) = channel();
 
@@ -26,7 +86,11 @@ fn main() -> Result<()> {
         }
     });
 
-    find_path(&maze, heuristic, tx)?;
+    let mut limits = SearchLimits::default();
+    if let Some(timeout) = timeout {
+        limits = limits.with_timeout(Duration::try_from_secs_f64(timeout)?);
+    }
+    find_path(&maze, heuristic, &args.search, &precomputed, limits, tx)?;
 
     match jh.join() {
         Ok(info) => {
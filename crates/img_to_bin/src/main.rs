@@ -8,17 +8,49 @@ use std::{
 use clap::{Parser, Subcommand};
 use rayon::prelude::*;
 
-use maze_exit_bin_common::{BinaryMazeWriter, MazeWriter, read_maze};
+use maze_exit_bin_common::{
+    ABSTRACTION_EXT, BinaryMazeWriter, Diagonal, ImportArgs, JUMP_TABLE_EXT, LANDMARKS_EXT,
+    MazeWriter, Selection, abstraction_path, jump_table_path, landmarks_path, read_maze,
+    write_abstraction, write_jump_table, write_landmarks,
+};
+use maze_exit_lib::{algorithm::Abstraction, generator::JumpTable, heuristics::Landmarks};
 
 const BINARY_EXT: &str = "bin";
 
 #[derive(Parser, Debug)]
 #[command(author = "Davide C. <davide.canton5@gmail.com>", version = "1.0")]
 struct Args {
+    #[command(flatten)]
+    import: ImportArgs,
+    #[command(flatten)]
+    precompute: PrecomputeArgs,
     #[command(subcommand)]
     subcmd: Commands,
 }
 
+/// Search data saved next to each binary file.
+#[derive(clap::Args, Debug, Clone, Copy)]
+struct PrecomputeArgs {
+    /// Also save the JPS+ jump table of each maze
+    #[arg(long = "jump-table", global = true)]
+    jump_table: bool,
+    /// Also save the HPA* abstraction of each maze
+    #[arg(long, global = true)]
+    abstraction: bool,
+    /// Side of the clusters of the saved abstractions
+    #[arg(long = "cluster-size", global = true, default_value_t = 32)]
+    cluster_size: u32,
+    /// Also save this many ALT landmarks of each maze
+    #[arg(long, global = true)]
+    landmarks: Option<usize>,
+    /// Where the saved landmarks are placed
+    #[arg(long = "landmark-selection", value_enum, global = true, default_value_t = Selection::Farthest)]
+    landmark_selection: Selection,
+    /// Diagonal moves allowed by the saved data
+    #[arg(long, value_enum, global = true, default_value_t = Diagonal::IfAtMostOneObstacle)]
+    diagonal: Diagonal,
+}
+
 #[derive(Debug, Subcommand)]
 enum Commands {
     File { src: PathBuf, dst: Option<PathBuf> },
@@ -29,18 +61,45 @@ fn main() -> Result<(), anyhow::Error> {
     let args = Args::parse();
 
     match args.subcmd {
-        Commands::File { src, dst } => handle_file(src, dst),
-        Commands::Dir { src, dst } => handle_dir(src, dst),
+        Commands::File { src, dst } => handle_file(src, dst, &args.import, args.precompute),
+        Commands::Dir { src, dst } => handle_dir(src, dst, &args.import, args.precompute),
     }
 }
 
-fn translate_single_file(fp: &Path, dst: &Path) -> Result<()> {
-    let maze = read_maze(fp)?;
-    let writer = File::open(dst)?;
-    BinaryMazeWriter.write_maze(&maze, writer)
+fn translate_single_file(
+    fp: &Path,
+    dst: &Path,
+    import: &ImportArgs,
+    precompute: PrecomputeArgs,
+) -> Result<()> {
+    let maze = read_maze(fp, import)?;
+    let writer = File::create(dst)?;
+    BinaryMazeWriter.write_maze(&maze, writer)?;
+
+    let movement = precompute.diagonal.into();
+    if precompute.jump_table {
+        let table = JumpTable::new(&maze, movement);
+        write_jump_table(&table, File::create(jump_table_path(dst))?)?;
+    }
+    if precompute.abstraction {
+        let abstraction = Abstraction::new(&maze, precompute.cluster_size, movement);
+        write_abstraction(&abstraction, File::create(abstraction_path(dst))?)?;
+    }
+    if let Some(count) = precompute.landmarks {
+        let landmarks =
+            Landmarks::new(&maze, count, precompute.landmark_selection.into(), movement);
+        write_landmarks(&landmarks, File::create(landmarks_path(dst))?)?;
+    }
+
+    Ok(())
 }
 
-fn handle_dir(src: PathBuf, dst: Option<PathBuf>) -> Result<(), anyhow::Error> {
+fn handle_dir(
+    src: PathBuf,
+    dst: Option<PathBuf>,
+    import: &ImportArgs,
+    precompute: PrecomputeArgs,
+) -> Result<(), anyhow::Error> {
     match metadata(&src) {
         Ok(metadata) if metadata.is_file() => {
             anyhow::bail!("Source is a file");
@@ -66,12 +125,19 @@ fn handle_dir(src: PathBuf, dst: Option<PathBuf>) -> Result<(), anyhow::Error> {
         .into_par_iter()
         .filter(|f| f.file_type().map(|t| t.is_file()).unwrap_or_default())
         .map(|f| f.path())
-        .filter(|f| f.extension().map(|v| v != BINARY_EXT).unwrap_or_default())
+        .filter(|f| {
+            f.extension()
+                .map(|v| {
+                    ![BINARY_EXT, JUMP_TABLE_EXT, ABSTRACTION_EXT, LANDMARKS_EXT]
+                        .contains(&v.to_str().unwrap_or_default())
+                })
+                .unwrap_or_default()
+        })
         .for_each(|fp| {
             let dst = &dst.join(fp.with_extension(BINARY_EXT).file_name().unwrap());
             println!("Translating {:?} to {:?}", fp, dst);
 
-            if let Err(e) = translate_single_file(&fp, dst) {
+            if let Err(e) = translate_single_file(&fp, dst, import, precompute) {
                 eprintln!("Error translating {:?}: {}", fp, e);
             }
         });
@@ -79,7 +145,12 @@ fn handle_dir(src: PathBuf, dst: Option<PathBuf>) -> Result<(), anyhow::Error> {
     Ok(())
 }
 
-fn handle_file(src: PathBuf, dst: Option<PathBuf>) -> Result<(), anyhow::Error> {
+fn handle_file(
+    src: PathBuf,
+    dst: Option<PathBuf>,
+    import: &ImportArgs,
+    precompute: PrecomputeArgs,
+) -> Result<(), anyhow::Error> {
     match metadata(&src) {
         Ok(metadata) if metadata.is_dir() => {
             anyhow::bail!("Source is a directory");
@@ -92,5 +163,5 @@ fn handle_file(src: PathBuf, dst: Option<PathBuf>) -> Result<(), anyhow::Error>
     }
 
     let dst = dst.unwrap_or_else(|| src.with_extension(BINARY_EXT));
-    translate_single_file(&src, &dst)
+    translate_single_file(&src, &dst, import, precompute)
 }
@@ -0,0 +1,195 @@
+use std::{
+    cmp::max,
+    collections::{BinaryHeap, HashMap, HashSet},
+};
+
+use crate::{
+    channel::ChannelSender,
+    cost::{Cost, scale_cost},
+    generator::ChildrenGenerator,
+    heuristics::MazeHeuristic,
+    position::Position,
+};
+
+use super::{
+    Child, Info, Message, QueueNode, SearchAlgorithm, SearchLimits, StopReason, build_path,
+};
+
+/// Anytime Repairing A*: runs weighted A* with a heuristic weight starting at
+/// `initial_epsilon` and lowered by `decrement` after every solution, down to 1.
+///
+/// Each search reuses the costs found by the previous ones, re-expanding only the nodes
+/// whose cost improved. Every improved path is sent as [`Message::Improved`] together with
+/// its suboptimality bound; the last one is also returned in [`Info`].
+pub struct AraStar {
+    pub initial_epsilon: f64,
+    pub decrement: f64,
+}
+
+impl AraStar {
+    pub fn new(initial_epsilon: f64, decrement: f64) -> Self {
+        AraStar {
+            initial_epsilon,
+            decrement,
+        }
+    }
+}
+
+struct State<'a> {
+    depth: HashMap<Position, Cost>,
+    parents: HashMap<Position, Position>,
+    queue: BinaryHeap<QueueNode>,
+    closed: HashSet<Position>,
+    inconsistent: HashSet<Position>,
+    heuristic: &'a dyn MazeHeuristic,
+    epsilon: f64,
+}
+
+impl SearchAlgorithm for AraStar {
+    fn search_with_limits<G: ChildrenGenerator + ?Sized, C: ChannelSender<Message>>(
+        &self,
+        start: Position,
+        goal: Position,
+        heuristic: &dyn MazeHeuristic,
+        generator: &G,
+        channel: C,
+        limits: &SearchLimits,
+    ) -> Info {
+        let mut info = Info::default();
+        let mut state = State {
+            depth: HashMap::from([(start, 0)]),
+            parents: HashMap::new(),
+            queue: BinaryHeap::new(),
+            closed: HashSet::new(),
+            inconsistent: HashSet::new(),
+            heuristic,
+            epsilon: self.initial_epsilon.max(1.0),
+        };
+        state.push(start, 0);
+
+        loop {
+            if let Some(stop) = state.improve_path(goal, generator, &channel, limits, &mut info) {
+                info.stop = stop;
+                return info;
+            }
+
+            let Some(&goal_depth) = state.depth.get(&goal) else {
+                return info;
+            };
+            info.stop = StopReason::Found;
+
+            let path = generator.reconstruct_path(&build_path(&state.parents, start, goal));
+            let bound = (goal_depth as f64 / state.min_unexpanded_f() as f64).min(state.epsilon);
+            if path.cost < info.path.as_ref().map_or(Cost::MAX, |p| p.cost) {
+                let improved = Message::Improved(path.clone(), bound.max(1.0));
+                if channel.send(improved).is_err() {
+                    info.path.replace(path);
+                    info.stop = StopReason::Disconnected;
+                    return info;
+                }
+                info.path.replace(path);
+            }
+
+            if state.epsilon <= 1.0 || self.decrement <= 0.0 {
+                return info;
+            }
+
+            state.epsilon = (state.epsilon - self.decrement).max(1.0);
+            state.restart();
+        }
+    }
+}
+
+impl State<'_> {
+    fn push(&mut self, node: Position, depth: Cost) {
+        let heuristic = scale_cost(self.heuristic.compute_heuristic(node), self.epsilon);
+        self.queue
+            .push(QueueNode::with_depth(node, heuristic, depth));
+    }
+
+    /// Runs weighted A* until the goal cannot be improved with the current weight. Returns
+    /// why it stopped if it did earlier.
+    fn improve_path<G: ChildrenGenerator + ?Sized, C: ChannelSender<Message>>(
+        &mut self,
+        goal: Position,
+        generator: &G,
+        channel: &C,
+        limits: &SearchLimits,
+        info: &mut Info,
+    ) -> Option<StopReason> {
+        while let Some(current) = self.queue.peek() {
+            let goal_depth = *self.depth.get(&goal).unwrap_or(&Cost::MAX);
+            if goal_depth <= current.depth + current.heuristic {
+                break;
+            }
+
+            let current = self.queue.pop().unwrap();
+            let current_node = current.node;
+            if self.closed.contains(&current_node) || current.depth > self.depth[&current_node] {
+                continue;
+            }
+
+            if let Some(stop) = limits.check(info.nodes, self.queue.len() + 1) {
+                return Some(stop);
+            }
+            info.nodes += 1;
+            info.max_length = max(info.max_length, self.queue.len());
+            self.closed.insert(current_node);
+
+            for Child {
+                node: successor,
+                weight,
+            } in
+                generator.generate_children(current_node, self.parents.get(&current_node).copied())
+            {
+                let successor_depth = current.depth + weight;
+                if successor_depth >= *self.depth.get(&successor).unwrap_or(&Cost::MAX) {
+                    continue;
+                }
+
+                self.depth.insert(successor, successor_depth);
+                self.parents.insert(successor, current_node);
+
+                if self.closed.contains(&successor) {
+                    self.inconsistent.insert(successor);
+                } else {
+                    if channel
+                        .send(Message::Enqueued(successor, successor_depth))
+                        .is_err()
+                    {
+                        return Some(StopReason::Disconnected);
+                    }
+                    self.push(successor, successor_depth);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Lowest unweighted `g + h` among the nodes still waiting to be expanded.
+    fn min_unexpanded_f(&self) -> Cost {
+        let f = |node: &Position| self.depth[node] + self.heuristic.compute_heuristic(*node);
+        self.queue
+            .iter()
+            .filter(|n| !self.closed.contains(&n.node) && n.depth <= self.depth[&n.node])
+            .map(|n| f(&n.node))
+            .chain(self.inconsistent.iter().map(f))
+            .fold(Cost::MAX, Cost::min)
+    }
+
+    /// Moves the inconsistent nodes back to the open list and re-keys it for the new weight.
+    fn restart(&mut self) {
+        let open = std::mem::take(&mut self.queue)
+            .into_iter()
+            .filter(|n| !self.closed.contains(&n.node) && n.depth <= self.depth[&n.node])
+            .map(|n| n.node)
+            .chain(self.inconsistent.drain())
+            .collect::<HashSet<_>>();
+
+        self.closed.clear();
+        for node in open {
+            self.push(node, self.depth[&node]);
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use std::sync::{Mutex, PoisonError};
+
+use crate::{
+    channel::ChannelSender, generator::ChildrenGenerator, heuristics::MazeHeuristic,
+    position::Position,
+};
+
+use super::{
+    Info, Message, QueueKind, Search, SearchAlgorithm, SearchBuffers, SearchLimits, TieBreak,
+};
+
+/// Best-first search expanding nodes in order of `depth_weight * g + heuristic_weight * h`,
+/// ties broken by `tie_break`, with its open list in a queue of kind `queue`. [`AStar`],
+/// [`WeightedAStar`], [`Dijkstra`] and [`GreedyBestFirst`] are its usual weights with the
+/// default tie-breaking and queue.
+///
+/// The buffers of the last search are kept for the next one, so that running it again on
+/// the same maze does not allocate them again.
+pub struct BestFirst {
+    pub depth_weight: f64,
+    pub heuristic_weight: f64,
+    pub tie_break: TieBreak,
+    pub queue: QueueKind,
+    buffers: Mutex<Option<SearchBuffers>>,
+}
+
+impl BestFirst {
+    pub fn new(depth_weight: f64, heuristic_weight: f64) -> Self {
+        BestFirst {
+            depth_weight,
+            heuristic_weight,
+            tie_break: TieBreak::default(),
+            queue: QueueKind::default(),
+            buffers: Mutex::new(None),
+        }
+    }
+
+    pub fn with_tie_break(self, tie_break: TieBreak) -> Self {
+        BestFirst { tie_break, ..self }
+    }
+
+    pub fn with_queue(self, queue: QueueKind) -> Self {
+        BestFirst { queue, ..self }
+    }
+}
+
+/// A* search: optimal with an admissible heuristic.
+pub struct AStar;
+
+/// A* with the heuristic inflated by `epsilon`, returning paths at most `epsilon` times
+/// longer than the optimal one while usually expanding far fewer nodes.
+pub struct WeightedAStar {
+    pub epsilon: f64,
+}
+
+impl WeightedAStar {
+    pub fn new(epsilon: f64) -> Self {
+        WeightedAStar { epsilon }
+    }
+}
+
+/// Dijkstra search: ignores the heuristic, optimal regardless of it.
+pub struct Dijkstra;
+
+/// Greedy best-first search: follows the heuristic only, fast but not optimal.
+pub struct GreedyBestFirst;
+
+impl SearchAlgorithm for AStar {
+    fn search_with_limits<G: ChildrenGenerator + ?Sized, C: ChannelSender<Message>>(
+        &self,
+        start: Position,
+        goal: Position,
+        heuristic: &dyn MazeHeuristic,
+        generator: &G,
+        channel: C,
+        limits: &SearchLimits,
+    ) -> Info {
+        BestFirst::new(1.0, 1.0)
+            .search_with_limits(start, goal, heuristic, generator, channel, limits)
+    }
+}
+
+impl SearchAlgorithm for WeightedAStar {
+    fn search_with_limits<G: ChildrenGenerator + ?Sized, C: ChannelSender<Message>>(
+        &self,
+        start: Position,
+        goal: Position,
+        heuristic: &dyn MazeHeuristic,
+        generator: &G,
+        channel: C,
+        limits: &SearchLimits,
+    ) -> Info {
+        BestFirst::new(1.0, self.epsilon)
+            .search_with_limits(start, goal, heuristic, generator, channel, limits)
+    }
+}
+
+impl SearchAlgorithm for Dijkstra {
+    fn search_with_limits<G: ChildrenGenerator + ?Sized, C: ChannelSender<Message>>(
+        &self,
+        start: Position,
+        goal: Position,
+        heuristic: &dyn MazeHeuristic,
+        generator: &G,
+        channel: C,
+        limits: &SearchLimits,
+    ) -> Info {
+        BestFirst::new(1.0, 0.0)
+            .search_with_limits(start, goal, heuristic, generator, channel, limits)
+    }
+}
+
+impl SearchAlgorithm for GreedyBestFirst {
+    fn search_with_limits<G: ChildrenGenerator + ?Sized, C: ChannelSender<Message>>(
+        &self,
+        start: Position,
+        goal: Position,
+        heuristic: &dyn MazeHeuristic,
+        generator: &G,
+        channel: C,
+        limits: &SearchLimits,
+    ) -> Info {
+        BestFirst::new(0.0, 1.0)
+            .search_with_limits(start, goal, heuristic, generator, channel, limits)
+    }
+}
+
+impl SearchAlgorithm for BestFirst {
+    fn search_with_limits<G: ChildrenGenerator + ?Sized, C: ChannelSender<Message>>(
+        &self,
+        start: Position,
+        goal: Position,
+        heuristic: &dyn MazeHeuristic,
+        generator: &G,
+        channel: C,
+        limits: &SearchLimits,
+    ) -> Info {
+        // taken rather than locked for the whole search, so that concurrent ones do not wait
+        let buffers = (self.buffers.lock())
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+            .filter(|b| b.queue() == self.queue)
+            .unwrap_or_else(|| SearchBuffers::new(self.queue));
+        let mut search = Search::with_buffers(
+            buffers,
+            start,
+            goal,
+            heuristic,
+            generator,
+            channel,
+            self.depth_weight,
+            self.heuristic_weight,
+        )
+        .with_tie_break(self.tie_break);
+        search.run_until(limits);
+        let (info, buffers) = search.into_parts();
+        *self.buffers.lock().unwrap_or_else(PoisonError::into_inner) = Some(buffers);
+        info
+    }
+}
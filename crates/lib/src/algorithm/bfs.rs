@@ -0,0 +1,81 @@
+use std::{
+    cmp::max,
+    collections::{HashMap, VecDeque},
+};
+
+use crate::{
+    channel::ChannelSender, generator::ChildrenGenerator, heuristics::MazeHeuristic,
+    position::Position,
+};
+
+use super::{Child, Info, Message, SearchAlgorithm, SearchLimits, StopReason, build_path};
+
+/// Breadth-first search: ignores both weights and heuristic, returning the path with the
+/// fewest generator steps.
+pub struct BreadthFirst;
+
+impl SearchAlgorithm for BreadthFirst {
+    fn search_with_limits<G: ChildrenGenerator + ?Sized, C: ChannelSender<Message>>(
+        &self,
+        start: Position,
+        goal: Position,
+        _heuristic: &dyn MazeHeuristic,
+        generator: &G,
+        channel: C,
+        limits: &SearchLimits,
+    ) -> Info {
+        let mut depth = HashMap::new();
+        let mut parents: HashMap<Position, Position> = HashMap::new();
+        let mut queue = VecDeque::new();
+        let mut info = Info::default();
+
+        depth.insert(start, 0);
+        queue.push_back(start);
+
+        while let Some(current) = queue.pop_front() {
+            if let Some(stop) = limits.check(info.nodes, queue.len() + 1) {
+                info.stop = stop;
+                return info;
+            }
+            info.nodes += 1;
+            info.max_length = max(info.max_length, queue.len());
+
+            if current == goal {
+                let path = build_path(&parents, start, goal);
+                info.path.replace(generator.reconstruct_path(&path));
+                info.stop = StopReason::Found;
+
+                return info;
+            }
+
+            let current_depth = depth[&current];
+
+            for generated in generator.generate_children(current, parents.get(&current).copied()) {
+                let Child {
+                    node: successor,
+                    weight,
+                } = generated;
+
+                if depth.contains_key(&successor) {
+                    continue;
+                }
+
+                let successor_depth = current_depth + weight;
+                parents.insert(successor, current);
+                depth.insert(successor, successor_depth);
+
+                if channel
+                    .send(Message::Enqueued(successor, successor_depth))
+                    .is_err()
+                {
+                    info.stop = StopReason::Disconnected;
+                    return info;
+                }
+
+                queue.push_back(successor);
+            }
+        }
+
+        info
+    }
+}
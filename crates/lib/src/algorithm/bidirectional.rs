@@ -0,0 +1,170 @@
+use std::{
+    cmp::max,
+    collections::{BinaryHeap, HashMap, HashSet},
+};
+
+use crate::{
+    channel::ChannelSender, cost::Cost, generator::ChildrenGenerator, heuristics::MazeHeuristic,
+    position::Position,
+};
+
+use super::{
+    Child, Info, Message, QueueNode, SearchAlgorithm, SearchLimits, StopReason, build_path,
+};
+
+/// Which of the two searches of [`BidirectionalAStar`] produced an event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Frontier {
+    Forward,
+    Backward,
+}
+
+/// A* run from the start and from the goal at the same time, stopping once no node left
+/// on either frontier can improve the best meeting point found so far.
+///
+/// Both searches expand children without a parent, so pruning generators such as
+/// `JpsGenerator` fall back to their natural neighbours and the two frontiers walk the same
+/// cell graph. The backward search uses `h(start) - h(n)` as its heuristic, which stays
+/// admissible whenever the forward heuristic is consistent.
+pub struct BidirectionalAStar;
+
+struct Side {
+    frontier: Frontier,
+    depth: HashMap<Position, Cost>,
+    parents: HashMap<Position, Position>,
+    queue: BinaryHeap<QueueNode>,
+    visited: HashSet<Position>,
+}
+
+impl Side {
+    fn new(frontier: Frontier, root: Position, heuristic: Cost) -> Self {
+        Side {
+            frontier,
+            depth: HashMap::from([(root, 0)]),
+            parents: HashMap::new(),
+            queue: BinaryHeap::from([QueueNode::new(root, heuristic)]),
+            visited: HashSet::new(),
+        }
+    }
+
+    fn min_f(&self) -> Cost {
+        self.queue
+            .peek()
+            .map_or(Cost::MAX, |n| n.heuristic + n.depth)
+    }
+}
+
+impl SearchAlgorithm for BidirectionalAStar {
+    fn search_with_limits<G: ChildrenGenerator + ?Sized, C: ChannelSender<Message>>(
+        &self,
+        start: Position,
+        goal: Position,
+        heuristic: &dyn MazeHeuristic,
+        generator: &G,
+        channel: C,
+        limits: &SearchLimits,
+    ) -> Info {
+        let start_h = heuristic.compute_heuristic(start);
+        let compute_heuristic = |frontier, node| {
+            let h = heuristic.compute_heuristic(node);
+            match frontier {
+                Frontier::Forward => h,
+                Frontier::Backward => start_h.saturating_sub(h),
+            }
+        };
+
+        let mut forward = Side::new(Frontier::Forward, start, start_h);
+        let mut backward = Side::new(Frontier::Backward, goal, 0);
+        let mut info = Info::default();
+
+        let mut best = Cost::MAX;
+        let mut meeting = None;
+        if start == goal {
+            best = 0;
+            meeting = Some(start);
+        }
+
+        while !forward.queue.is_empty() && !backward.queue.is_empty() {
+            if forward.min_f() >= best || backward.min_f() >= best {
+                break;
+            }
+
+            let (side, other) = if forward.queue.len() <= backward.queue.len() {
+                (&mut forward, &backward)
+            } else {
+                (&mut backward, &forward)
+            };
+
+            let current = side.queue.pop().unwrap().node;
+            if !side.visited.insert(current) {
+                continue;
+            }
+
+            let queued = side.queue.len() + other.queue.len();
+            if let Some(stop) = limits.check(info.nodes, queued + 1) {
+                info.stop = stop;
+                return info;
+            }
+            info.nodes += 1;
+            info.max_length = max(info.max_length, queued);
+
+            let current_depth = side.depth[&current];
+
+            for Child {
+                node: successor,
+                weight,
+            } in generator.generate_children(current, None)
+            {
+                if side.visited.contains(&successor) {
+                    continue;
+                }
+
+                let successor_depth = current_depth + weight;
+                let ex_depth = *side.depth.get(&successor).unwrap_or(&Cost::MAX);
+                if successor_depth >= ex_depth {
+                    continue;
+                }
+
+                side.parents.insert(successor, current);
+                side.depth.insert(successor, successor_depth);
+
+                if let Some(other_depth) = other.depth.get(&successor)
+                    && successor_depth + other_depth < best
+                {
+                    best = successor_depth + other_depth;
+                    meeting = Some(successor);
+                }
+
+                if channel
+                    .send(Message::FrontierEnqueued(
+                        side.frontier,
+                        successor,
+                        successor_depth,
+                    ))
+                    .is_err()
+                {
+                    info.stop = StopReason::Disconnected;
+                    return info;
+                }
+
+                side.queue.push(QueueNode::with_depth(
+                    successor,
+                    compute_heuristic(side.frontier, successor),
+                    successor_depth,
+                ));
+            }
+        }
+
+        if let Some(meeting) = meeting {
+            let mut path = build_path(&forward.parents, start, meeting);
+            let mut tail = build_path(&backward.parents, goal, meeting);
+            tail.pop();
+            path.extend(tail.into_iter().rev());
+
+            info.path.replace(generator.reconstruct_path(&path));
+            info.stop = StopReason::Found;
+        }
+
+        info
+    }
+}
@@ -0,0 +1,547 @@
+use std::{collections::HashMap, mem};
+
+use itertools::Itertools;
+
+use crate::{
+    channel::{ChannelSender, noop_sender},
+    cost::Cost,
+    generator::{
+        ChildrenGenerator, DiagonalMovement, JpsGenerator, MazePathRef, NodeIndex, PathInfo,
+    },
+    heuristics::{MazeHeuristic, OctileHeuristic},
+    maze::Maze,
+    position::Position,
+};
+
+use super::{
+    BestFirst, Child, Info, Message, QueueKind, Search, SearchAlgorithm, SearchBuffers,
+    SearchLimits, StopReason,
+};
+
+/// Entrances shorter than this get a single transition in their middle, longer ones one
+/// at each end.
+const MAX_SINGLE_TRANSITION: usize = 6;
+
+/// Abstract graph of a maze split into square clusters, used by [`HpaStar`].
+///
+/// Nodes are the cells on both sides of the transitions between adjacent clusters. Edges
+/// link the two sides of each transition, and every pair of nodes of a cluster connected
+/// inside it, with the cost of the shortest path between them within the cluster.
+#[derive(Clone, Debug)]
+pub struct Abstraction {
+    width: u32,
+    height: u32,
+    cluster_size: u32,
+    movement: DiagonalMovement,
+    fingerprint: u64,
+    nodes: Vec<Position>,
+    edges: Vec<(u32, u32, Cost)>,
+    index: HashMap<Position, usize>,
+    adjacency: Vec<Vec<(usize, Cost)>>,
+    clusters: Vec<Vec<usize>>,
+}
+
+impl Abstraction {
+    pub fn new(maze: &Maze, cluster_size: u32, movement: DiagonalMovement) -> Self {
+        let mut abstraction = Abstraction::empty(maze, cluster_size, movement);
+
+        for (cx, cy) in abstraction.cluster_coords() {
+            let (origin, size) = abstraction.cluster_rect(cx, cy);
+            let end = origin + size;
+            if end.x < maze.width() as i32 {
+                abstraction.add_entrances(
+                    maze,
+                    (origin.y..end.y).map(|y| Position::new(end.x - 1, y)),
+                    Position::X,
+                );
+            }
+            if end.y < maze.height() as i32 {
+                abstraction.add_entrances(
+                    maze,
+                    (origin.x..end.x).map(|x| Position::new(x, end.y - 1)),
+                    Position::Y,
+                );
+            }
+        }
+
+        for (cx, cy) in abstraction.cluster_coords() {
+            let (origin, size) = abstraction.cluster_rect(cx, cy);
+            let mut cluster = Cluster::new(maze, origin, size, movement, QueueKind::default());
+            let nodes = abstraction.clusters[abstraction.cluster_id(origin)].clone();
+            for (j, &to) in nodes.iter().enumerate() {
+                for &from in &nodes[..j] {
+                    let (path, _) = cluster.search(abstraction.nodes[from], abstraction.nodes[to]);
+                    if let Some(path) = path {
+                        abstraction.add_edge(from, to, path.cost);
+                        abstraction.add_edge(to, from, path.cost);
+                    }
+                }
+            }
+        }
+
+        abstraction
+    }
+
+    /// Rebuilds an abstraction from the values returned by the accessors of a saved one.
+    pub fn from_parts(
+        width: u32,
+        height: u32,
+        cluster_size: u32,
+        movement: DiagonalMovement,
+        fingerprint: u64,
+        nodes: Vec<Position>,
+        edges: Vec<(u32, u32, Cost)>,
+    ) -> Result<Self, String> {
+        if cluster_size == 0 {
+            return Err("Invalid abstraction: empty clusters".to_string());
+        }
+
+        let mut abstraction = Abstraction {
+            width,
+            height,
+            cluster_size,
+            movement,
+            fingerprint,
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            index: HashMap::new(),
+            adjacency: Vec::new(),
+            clusters: vec![Vec::new(); abstraction_clusters(width, height, cluster_size)],
+        };
+
+        for pos in nodes {
+            if !(0..width as i32).contains(&pos.x) || !(0..height as i32).contains(&pos.y) {
+                return Err(format!("Invalid abstraction: node {} out of the maze", pos));
+            }
+            abstraction.add_node(pos);
+        }
+        for (from, to, cost) in edges {
+            let len = abstraction.nodes.len() as u32;
+            if from >= len || to >= len {
+                return Err(format!("Invalid abstraction: edge {}-{}", from, to));
+            }
+            abstraction.add_edge(from as usize, to as usize, cost);
+        }
+
+        Ok(abstraction)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn cluster_size(&self) -> u32 {
+        self.cluster_size
+    }
+
+    pub fn movement(&self) -> DiagonalMovement {
+        self.movement
+    }
+
+    /// [`Maze::fingerprint`] of the maze the abstraction was built for.
+    pub fn fingerprint(&self) -> u64 {
+        self.fingerprint
+    }
+
+    pub fn nodes(&self) -> &[Position] {
+        &self.nodes
+    }
+
+    /// Edges as pairs of indices into [`Self::nodes`] with their cost, in both directions.
+    pub fn edges(&self) -> &[(u32, u32, Cost)] {
+        &self.edges
+    }
+
+    /// Whether the abstraction was built for a maze with the same walls and costs as `maze`.
+    pub fn matches(&self, maze: &Maze) -> bool {
+        self.width == maze.width()
+            && self.height == maze.height()
+            && self.fingerprint == maze.fingerprint()
+    }
+
+    fn empty(maze: &Maze, cluster_size: u32, movement: DiagonalMovement) -> Self {
+        Abstraction::from_parts(
+            maze.width(),
+            maze.height(),
+            cluster_size,
+            movement,
+            maze.fingerprint(),
+            Vec::new(),
+            Vec::new(),
+        )
+        .expect("valid empty abstraction")
+    }
+
+    fn cluster_coords(&self) -> impl Iterator<Item = (u32, u32)> + use<> {
+        let columns = self.width.div_ceil(self.cluster_size);
+        let rows = self.height.div_ceil(self.cluster_size);
+        (0..rows).flat_map(move |cy| (0..columns).map(move |cx| (cx, cy)))
+    }
+
+    /// Origin and size of a cluster, smaller than the others on the right and bottom edges.
+    fn cluster_rect(&self, cx: u32, cy: u32) -> (Position, Position) {
+        let size = self.cluster_size;
+        let origin = Position::new((cx * size) as i32, (cy * size) as i32);
+        let end = Position::new(
+            ((cx + 1) * size).min(self.width) as i32,
+            ((cy + 1) * size).min(self.height) as i32,
+        );
+        (origin, end - origin)
+    }
+
+    fn cluster_id(&self, pos: Position) -> usize {
+        let columns = self.width.div_ceil(self.cluster_size) as usize;
+        let size = self.cluster_size as usize;
+        pos.y as usize / size * columns + pos.x as usize / size
+    }
+
+    fn add_node(&mut self, pos: Position) -> usize {
+        if let Some(&i) = self.index.get(&pos) {
+            return i;
+        }
+
+        let i = self.nodes.len();
+        self.nodes.push(pos);
+        self.index.insert(pos, i);
+        self.adjacency.push(Vec::new());
+        let cluster = self.cluster_id(pos);
+        self.clusters[cluster].push(i);
+        i
+    }
+
+    fn add_edge(&mut self, from: usize, to: usize, cost: Cost) {
+        self.edges.push((from as u32, to as u32, cost));
+        self.adjacency[from].push((to, cost));
+    }
+
+    /// Adds the transitions between the cells of `border` and their neighbours along
+    /// `across`, in the next cluster.
+    fn add_entrances(
+        &mut self,
+        maze: &Maze,
+        border: impl Iterator<Item = Position>,
+        across: Position,
+    ) {
+        let open = |&pos: &Position| maze.is_free(pos) && maze.is_free(pos + across);
+        let runs = border.chunk_by(open);
+        for (_, run) in runs.into_iter().filter(|(open, _)| *open) {
+            let run: Vec<_> = run.collect();
+            let transitions = if run.len() < MAX_SINGLE_TRANSITION {
+                vec![run[run.len() / 2]]
+            } else {
+                vec![run[0], run[run.len() - 1]]
+            };
+
+            for pos in transitions {
+                let (a, b) = (self.add_node(pos), self.add_node(pos + across));
+                let cost = maze.step_cost(pos, pos + across);
+                self.add_edge(a, b, cost);
+                self.add_edge(b, a, cost);
+            }
+        }
+    }
+}
+
+fn abstraction_clusters(width: u32, height: u32, cluster_size: u32) -> usize {
+    width.div_ceil(cluster_size) as usize * height.div_ceil(cluster_size) as usize
+}
+
+/// Copy of the cells of a cluster, searched with [`Search`] and [`JpsGenerator`].
+struct Cluster {
+    maze: Maze,
+    origin: Position,
+    movement: DiagonalMovement,
+    buffers: SearchBuffers,
+}
+
+impl Cluster {
+    fn new(
+        maze: &Maze,
+        origin: Position,
+        size: Position,
+        movement: DiagonalMovement,
+        queue: QueueKind,
+    ) -> Self {
+        let mut cluster = Maze::new(size.x as u32, size.y as u32, Position::ZERO, Position::ZERO);
+        for y in 0..size.y {
+            for x in 0..size.x {
+                let local = Position::new(x, y);
+                let pos = origin + local;
+                if maze.is_wall(pos) {
+                    cluster.set(local, true).unwrap();
+                } else if !maze.has_uniform_costs() {
+                    cluster.set_cost(local, maze.cost(pos)).unwrap();
+                }
+            }
+        }
+
+        Cluster {
+            maze: cluster,
+            origin,
+            movement,
+            buffers: SearchBuffers::new(queue),
+        }
+    }
+
+    /// Shortest path between two cells of the cluster without leaving it, and the number of
+    /// nodes expanded to find it.
+    fn search(&mut self, from: Position, to: Position) -> (Option<PathInfo>, u32) {
+        if from == to {
+            return (Some(PathInfo::new(vec![from], 0)), 0);
+        }
+
+        let (from, to) = (from - self.origin, to - self.origin);
+        self.maze.set_goal(to).unwrap();
+        // an exact heuristic, so that edge costs are the shortest distances in the cluster
+        let heuristic = OctileHeuristic::with_movement(&self.maze, self.movement);
+        let generator = JpsGenerator::with_movement(&self.maze, self.movement);
+        let buffers = mem::take(&mut self.buffers);
+        let mut search = Search::with_buffers(
+            buffers,
+            from,
+            to,
+            &heuristic,
+            &generator,
+            noop_sender(),
+            1.0,
+            1.0,
+        );
+        search.run_until(&SearchLimits::default());
+        let (info, buffers) = search.into_parts();
+        self.buffers = buffers;
+
+        let path = info.path.map(|mut p| {
+            p.path.iter_mut().for_each(|pos| *pos += self.origin);
+            p
+        });
+        (path, info.nodes)
+    }
+}
+
+/// The abstract graph with start and goal linked to the nodes of their clusters.
+///
+/// Nodes are numbered by their index in the abstraction, then the start and the goal.
+struct QueryGraph<'a> {
+    abstraction: &'a Abstraction,
+    start: Position,
+    goal: Position,
+    extra: HashMap<Position, Vec<Child>>,
+}
+
+impl ChildrenGenerator for QueryGraph<'_> {
+    fn generate_children(&self, current: Position, _parent: Option<Position>) -> Vec<Child> {
+        let nodes = &self.abstraction.nodes;
+        let mut children: Vec<_> = self
+            .abstraction
+            .index
+            .get(&current)
+            .map(|&i| &self.abstraction.adjacency[i])
+            .into_iter()
+            .flatten()
+            .map(|&(n, cost)| Child::new(nodes[n], cost))
+            .collect();
+        if let Some(extra) = self.extra.get(&current) {
+            children.extend(extra.iter().map(|c| Child::new(c.node, c.weight)));
+        }
+        children
+    }
+
+    fn reconstruct_path(&self, path: MazePathRef) -> PathInfo {
+        let cost = path
+            .iter()
+            .tuple_windows()
+            .map(|(&a, &b)| {
+                self.generate_children(a, None)
+                    .into_iter()
+                    .filter(|c| c.node == b)
+                    .map(|c| c.weight)
+                    .fold(Cost::MAX, Cost::min)
+            })
+            .sum();
+        PathInfo::new(path.to_vec(), cost)
+    }
+
+    fn node_index(&self) -> Option<&dyn NodeIndex> {
+        Some(self)
+    }
+}
+
+impl NodeIndex for QueryGraph<'_> {
+    fn node_count(&self) -> usize {
+        self.abstraction.nodes.len() + 2
+    }
+
+    fn index_of(&self, node: Position) -> Option<usize> {
+        let count = self.abstraction.nodes.len();
+        self.abstraction
+            .index
+            .get(&node)
+            .copied()
+            .or_else(|| (node == self.start).then_some(count))
+            .or_else(|| (node == self.goal).then_some(count + 1))
+    }
+
+    fn node_at(&self, index: usize) -> Position {
+        let nodes = &self.abstraction.nodes;
+        match index.checked_sub(nodes.len()) {
+            None => nodes[index],
+            Some(0) => self.start,
+            Some(_) => self.goal,
+        }
+    }
+}
+
+/// Hierarchical path-finding A*: searches the [`Abstraction`] of the maze, then refines
+/// each abstract edge with a search inside its cluster.
+///
+/// Paths are near-optimal: they only cross between clusters at the transitions. The
+/// generator is not used: the edge costs of the abstraction were found with [`JpsGenerator`]
+/// and the [`DiagonalMovement`] of the abstraction, so cluster searches use them too, while
+/// `heuristic` guides the abstract search. The search stops with
+/// [`StopReason::Unrefinable`] if an abstract edge has no path in the maze.
+/// [`Info::abstract_nodes`] and [`Info::refined_nodes`] count the nodes expanded at each
+/// level. Both levels keep their open lists in queues of kind `queue`.
+pub struct HpaStar<'a> {
+    pub maze: &'a Maze,
+    pub abstraction: &'a Abstraction,
+    pub queue: QueueKind,
+}
+
+impl<'a> HpaStar<'a> {
+    /// Fails if `abstraction` was built for another maze.
+    pub fn new(maze: &'a Maze, abstraction: &'a Abstraction) -> Result<Self, String> {
+        if !abstraction.matches(maze) {
+            return Err("Abstraction built for another maze".to_string());
+        }
+
+        Ok(HpaStar {
+            maze,
+            abstraction,
+            queue: QueueKind::default(),
+        })
+    }
+
+    pub fn with_queue(self, queue: QueueKind) -> Self {
+        HpaStar { queue, ..self }
+    }
+
+    /// The cluster of `pos`, copied from the maze the first time a query needs it.
+    fn cluster<'c>(
+        &self,
+        clusters: &'c mut HashMap<usize, Cluster>,
+        pos: Position,
+    ) -> &'c mut Cluster {
+        clusters
+            .entry(self.abstraction.cluster_id(pos))
+            .or_insert_with(|| {
+                let size = self.abstraction.cluster_size as i32;
+                let (cx, cy) = (pos.x / size, pos.y / size);
+                let (origin, size) = self.abstraction.cluster_rect(cx as u32, cy as u32);
+                Cluster::new(
+                    self.maze,
+                    origin,
+                    size,
+                    self.abstraction.movement,
+                    self.queue,
+                )
+            })
+    }
+
+    /// Links `start` and `goal` to the nodes of their clusters, and to each other if they
+    /// share one.
+    fn query_graph(
+        &self,
+        start: Position,
+        goal: Position,
+        clusters: &mut HashMap<usize, Cluster>,
+        info: &mut Info,
+    ) -> QueryGraph<'_> {
+        let abstraction = self.abstraction;
+        let mut extra: HashMap<Position, Vec<Child>> = HashMap::new();
+        let mut link = |cluster: &mut Cluster, from: Position, to: Position, info: &mut Info| {
+            let (path, nodes) = cluster.search(from, to);
+            info.refined_nodes += nodes;
+            if let Some(path) = path {
+                extra
+                    .entry(from)
+                    .or_default()
+                    .push(Child::new(to, path.cost));
+            }
+        };
+
+        let start_cluster = self.cluster(clusters, start);
+        for &n in &abstraction.clusters[abstraction.cluster_id(start)] {
+            link(start_cluster, start, abstraction.nodes[n], info);
+        }
+        let goal_cluster = self.cluster(clusters, goal);
+        for &n in &abstraction.clusters[abstraction.cluster_id(goal)] {
+            link(goal_cluster, abstraction.nodes[n], goal, info);
+        }
+        if abstraction.cluster_id(start) == abstraction.cluster_id(goal) {
+            link(goal_cluster, start, goal, info);
+        }
+
+        QueryGraph {
+            abstraction,
+            start,
+            goal,
+            extra,
+        }
+    }
+}
+
+impl SearchAlgorithm for HpaStar<'_> {
+    fn search_with_limits<G: ChildrenGenerator + ?Sized, C: ChannelSender<Message>>(
+        &self,
+        start: Position,
+        goal: Position,
+        heuristic: &dyn MazeHeuristic,
+        _generator: &G,
+        channel: C,
+        limits: &SearchLimits,
+    ) -> Info {
+        let mut info = Info::default();
+        let mut clusters = HashMap::new();
+        let graph = self.query_graph(start, goal, &mut clusters, &mut info);
+
+        // the refinement is bounded by the size of the clusters, only the abstract search
+        // is limited
+        let abstract_info = BestFirst::new(1.0, 1.0)
+            .with_queue(self.queue)
+            .search_with_limits(start, goal, heuristic, &graph, channel, limits);
+        info.abstract_nodes = abstract_info.nodes;
+        info.max_length = abstract_info.max_length;
+        info.stop = abstract_info.stop;
+
+        if let Some(abstract_path) = abstract_info.path {
+            let mut path = vec![start];
+            let mut cost = 0;
+            for (&a, &b) in abstract_path.path.iter().tuple_windows() {
+                let crossing = self.abstraction.cluster_id(a) != self.abstraction.cluster_id(b);
+                if crossing {
+                    cost += self.maze.step_cost(a, b);
+                    path.push(b);
+                    continue;
+                }
+
+                let (refined, nodes) = self.cluster(&mut clusters, a).search(a, b);
+                info.refined_nodes += nodes;
+                let Some(refined) = refined else {
+                    info.stop = StopReason::Unrefinable;
+                    info.nodes = info.abstract_nodes + info.refined_nodes;
+                    return info;
+                };
+                cost += refined.cost;
+                path.extend_from_slice(&refined.path[1..]);
+            }
+            info.path = Some(PathInfo::new(path, cost));
+        }
+
+        info.nodes = info.abstract_nodes + info.refined_nodes;
+        info
+    }
+}
@@ -0,0 +1,127 @@
+use std::{cmp::max, collections::HashSet};
+
+use crate::{
+    channel::ChannelSender, cost::Cost, generator::ChildrenGenerator, heuristics::MazeHeuristic,
+    position::Position,
+};
+
+use super::{Child, Info, Message, SearchAlgorithm, SearchLimits, StopReason};
+
+/// Iterative deepening A*: repeated depth-first searches bounded by `g + h`, raising the
+/// bound to the smallest value that exceeded it. Only the current path is kept in memory,
+/// at the price of expanding nodes again in every iteration.
+pub struct IdaStar;
+
+struct Frame {
+    node: Position,
+    depth: Cost,
+    children: Vec<Child>,
+}
+
+enum Outcome {
+    Found(Vec<Position>),
+    Bound(Cost),
+    Stopped(StopReason),
+}
+
+impl SearchAlgorithm for IdaStar {
+    fn search_with_limits<G: ChildrenGenerator + ?Sized, C: ChannelSender<Message>>(
+        &self,
+        start: Position,
+        goal: Position,
+        heuristic: &dyn MazeHeuristic,
+        generator: &G,
+        channel: C,
+        limits: &SearchLimits,
+    ) -> Info {
+        let mut info = Info::default();
+        let mut bound = heuristic.compute_heuristic(start);
+
+        loop {
+            match bounded_search(
+                start, goal, bound, heuristic, generator, &channel, limits, &mut info,
+            ) {
+                Outcome::Found(path) => {
+                    info.path.replace(generator.reconstruct_path(&path));
+                    info.stop = StopReason::Found;
+                    return info;
+                }
+                Outcome::Bound(next) if next != Cost::MAX => bound = next,
+                Outcome::Bound(_) => return info,
+                Outcome::Stopped(stop) => {
+                    info.stop = stop;
+                    return info;
+                }
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn bounded_search<G: ChildrenGenerator + ?Sized, C: ChannelSender<Message>>(
+    start: Position,
+    goal: Position,
+    bound: Cost,
+    heuristic: &dyn MazeHeuristic,
+    generator: &G,
+    channel: &C,
+    limits: &SearchLimits,
+    info: &mut Info,
+) -> Outcome {
+    if start == goal {
+        return Outcome::Found(vec![start]);
+    }
+
+    let mut next_bound = Cost::MAX;
+    let mut on_path = HashSet::from([start]);
+    let mut stack = vec![Frame {
+        node: start,
+        depth: 0,
+        children: generator.generate_children(start, None),
+    }];
+    info.nodes += 1;
+
+    while let Some(frame) = stack.last_mut() {
+        let Some(Child { node, weight }) = frame.children.pop() else {
+            on_path.remove(&frame.node);
+            stack.pop();
+            continue;
+        };
+
+        if on_path.contains(&node) {
+            continue;
+        }
+
+        let current = frame.node;
+        let depth = frame.depth + weight;
+        let f = depth + heuristic.compute_heuristic(node);
+        if f > bound {
+            next_bound = next_bound.min(f);
+            continue;
+        }
+
+        if channel.send(Message::Enqueued(node, depth)).is_err() {
+            return Outcome::Stopped(StopReason::Disconnected);
+        }
+
+        if node == goal {
+            let mut path: Vec<_> = stack.iter().map(|f| f.node).collect();
+            path.push(goal);
+            return Outcome::Found(path);
+        }
+
+        if let Some(stop) = limits.check(info.nodes, stack.len()) {
+            return Outcome::Stopped(stop);
+        }
+        info.nodes += 1;
+        on_path.insert(node);
+        stack.push(Frame {
+            node,
+            depth,
+            children: generator.generate_children(node, Some(current)),
+        });
+        info.max_length = max(info.max_length, stack.len());
+    }
+
+    Outcome::Bound(next_bound)
+}
@@ -0,0 +1,96 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+/// Why a search stopped, recorded in [`Info`](super::Info).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StopReason {
+    /// Nothing left to explore without reaching the goal.
+    #[default]
+    Exhausted,
+    /// The goal was reached.
+    Found,
+    /// The deadline of the [`SearchLimits`] passed.
+    Deadline,
+    /// As many nodes as allowed by the [`SearchLimits`] were expanded.
+    MaxExpanded,
+    /// The queue grew past the size allowed by the [`SearchLimits`].
+    MaxQueue,
+    /// The cancellation flag of the [`SearchLimits`] was raised.
+    Cancelled,
+    /// The receiver of the messages hung up.
+    Disconnected,
+    /// An edge of a path over precomputed data had no path in the maze, as happens with data
+    /// built for another maze.
+    Unrefinable,
+}
+
+/// Bounds on the time and memory of a search, checked before each expansion. There are
+/// none by default.
+#[derive(Clone, Debug, Default)]
+pub struct SearchLimits {
+    pub deadline: Option<Instant>,
+    pub max_expanded: Option<u32>,
+    pub max_queue: Option<usize>,
+    /// Stops the search once set, from another thread.
+    pub cancel: Option<Arc<AtomicBool>>,
+}
+
+impl SearchLimits {
+    pub fn with_deadline(self, deadline: Instant) -> Self {
+        SearchLimits {
+            deadline: Some(deadline),
+            ..self
+        }
+    }
+
+    /// Sets the deadline `timeout` from now.
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        self.with_deadline(Instant::now() + timeout)
+    }
+
+    pub fn with_max_expanded(self, max_expanded: u32) -> Self {
+        SearchLimits {
+            max_expanded: Some(max_expanded),
+            ..self
+        }
+    }
+
+    pub fn with_max_queue(self, max_queue: usize) -> Self {
+        SearchLimits {
+            max_queue: Some(max_queue),
+            ..self
+        }
+    }
+
+    pub fn with_cancel(self, cancel: Arc<AtomicBool>) -> Self {
+        SearchLimits {
+            cancel: Some(cancel),
+            ..self
+        }
+    }
+
+    /// The limit reached by a search that expanded `expanded` nodes and has `queued` ones
+    /// waiting, if any.
+    pub fn check(&self, expanded: u32, queued: usize) -> Option<StopReason> {
+        if self
+            .cancel
+            .as_ref()
+            .is_some_and(|c| c.load(Ordering::Relaxed))
+        {
+            Some(StopReason::Cancelled)
+        } else if self.max_expanded.is_some_and(|max| expanded >= max) {
+            Some(StopReason::MaxExpanded)
+        } else if self.max_queue.is_some_and(|max| queued > max) {
+            Some(StopReason::MaxQueue)
+        } else if self.deadline.is_some_and(|d| Instant::now() >= d) {
+            Some(StopReason::Deadline)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,170 @@
+mod ara;
+mod best_first;
+mod bfs;
+mod bidirectional;
+mod hpa;
+mod ida;
+mod limits;
+mod queue;
+mod search;
+mod sma;
+mod theta;
+
+use std::{cmp::Ordering, collections::HashMap, time::Duration};
+
+use crate::{
+    channel::ChannelSender,
+    cost::Cost,
+    generator::{ChildrenGenerator, MazePath, PathInfo},
+    heuristics::MazeHeuristic,
+    position::Position,
+};
+
+pub use ara::AraStar;
+pub use best_first::{AStar, BestFirst, Dijkstra, GreedyBestFirst, WeightedAStar};
+pub use bfs::BreadthFirst;
+pub use bidirectional::{BidirectionalAStar, Frontier};
+pub use hpa::{Abstraction, HpaStar};
+pub use ida::IdaStar;
+pub use limits::{SearchLimits, StopReason};
+pub use queue::{
+    BucketQueue, IndexedHeap, PairingHeap, PriorityQueue, QueueKey, QueueKind, RadixHeap,
+};
+pub use search::{Search, SearchBuffers, Step, TieBreak};
+pub use sma::SmaStar;
+pub use theta::{LazyThetaStar, ThetaStar};
+
+#[derive(Default, Debug)]
+pub struct Info {
+    pub max_length: usize,
+    pub nodes: u32,
+    pub path: Option<PathInfo>,
+    pub time: Duration,
+    /// Nodes expanded on the abstract graph of a hierarchical search.
+    pub abstract_nodes: u32,
+    /// Nodes expanded by a hierarchical search inside clusters, to link start and goal to
+    /// the abstract graph and to refine its path.
+    pub refined_nodes: u32,
+    pub stop: StopReason,
+}
+
+/// Entry of the open list, ordered by `heuristic + depth` (lowest first), then as with
+/// [`TieBreak::HigherG`]: deepest first, then in row-major order.
+#[derive(Debug)]
+pub struct QueueNode {
+    pub heuristic: Cost,
+    pub node: Position,
+    pub depth: Cost,
+}
+
+impl QueueNode {
+    fn new(node: Position, heuristic: Cost) -> Self {
+        QueueNode::with_depth(node, heuristic, 0)
+    }
+
+    fn with_depth(node: Position, heuristic: Cost, depth: Cost) -> Self {
+        QueueNode {
+            heuristic,
+            node,
+            depth,
+        }
+    }
+}
+
+impl PartialEq for QueueNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for QueueNode {}
+
+impl PartialOrd for QueueNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for QueueNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let h1 = self.heuristic + self.depth;
+        let h2 = other.heuristic + other.depth;
+        h1.cmp(&h2)
+            .then(other.depth.cmp(&self.depth))
+            .then((self.node.y, self.node.x).cmp(&(other.node.y, other.node.x)))
+            .reverse()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Child {
+    pub node: Position,
+    pub weight: Cost,
+}
+
+impl Child {
+    pub fn new(node: Position, weight: Cost) -> Self {
+        Child { node, weight }
+    }
+}
+
+pub enum Message {
+    Enqueued(Position, Cost),
+    /// Like `Enqueued`, for searches that grow more than one frontier.
+    FrontierEnqueued(Frontier, Position, Cost),
+    /// A better path found by an anytime search, with an upper bound on the ratio between
+    /// its cost and the optimal one.
+    Improved(PathInfo, f64),
+    End(Info),
+}
+
+/// A search strategy over the graph described by a [`ChildrenGenerator`].
+pub trait SearchAlgorithm {
+    fn search<G: ChildrenGenerator + ?Sized, C: ChannelSender<Message>>(
+        &self,
+        start: Position,
+        goal: Position,
+        heuristic: &dyn MazeHeuristic,
+        generator: &G,
+        channel: C,
+    ) -> Info {
+        let limits = SearchLimits::default();
+        self.search_with_limits(start, goal, heuristic, generator, channel, &limits)
+    }
+
+    /// Like [`SearchAlgorithm::search`], stopping early once one of `limits` is reached.
+    fn search_with_limits<G: ChildrenGenerator + ?Sized, C: ChannelSender<Message>>(
+        &self,
+        start: Position,
+        goal: Position,
+        heuristic: &dyn MazeHeuristic,
+        generator: &G,
+        channel: C,
+        limits: &SearchLimits,
+    ) -> Info;
+}
+
+pub fn a_star<G: ChildrenGenerator + ?Sized, C: ChannelSender<Message>>(
+    start: Position,
+    goal: Position,
+    heuristic: &dyn MazeHeuristic,
+    generator: &G,
+    channel: C,
+) -> Info {
+    let mut search = Search::new(start, goal, heuristic, generator, channel);
+    search.run_until(&SearchLimits::default());
+    search.into_info()
+}
+
+/// Walks `parents` back from `goal`, returning the nodes from `start` to `goal`.
+fn build_path(parents: &HashMap<Position, Position>, start: Position, goal: Position) -> MazePath {
+    let mut node = goal;
+    let mut path = Vec::new();
+    while parents.contains_key(&node) {
+        path.push(node);
+        node = *parents.get(&node).expect("not found in parents");
+    }
+    path.push(start);
+    path.reverse();
+    path
+}
@@ -0,0 +1,314 @@
+use std::{iter, mem};
+
+use crate::cost::{COST_SCALE, Cost};
+
+use super::{IndexedHeap, PriorityQueue, QueueKey};
+
+const NONE: usize = usize::MAX;
+
+const ABSENT: usize = 0;
+
+/// Costs of a bucket: one orthogonal step, so that the moves between neighbouring cells
+/// land in the next few buckets.
+const BUCKET_WIDTH: Cost = COST_SCALE;
+
+/// Buckets of the ring at most, for 512 KiB of list heads.
+const MAX_BUCKETS: usize = 1 << 16;
+
+/// Monotone bucket queue, as in Dial's algorithm: a ring with one bucket per
+/// [`COST_SCALE`] of cost above the last popped one, each bucket a list linked through the
+/// items.
+///
+/// Insertions and key decreases take constant time, removals skip the empty buckets 64 at
+/// a time. The ring grows to the span of the costs in the queue, up to 2^16 buckets; the
+/// items farther away wait in a binary heap, so long jumps cost a heap operation instead of
+/// memory. The items of the lowest bucket, or lower, are in another binary heap that orders
+/// them exactly.
+#[derive(Clone, Debug)]
+pub struct BucketQueue {
+    /// The bucket of the last cost popped, or of the lowest pushed since the queue was
+    /// emptied: the bucket at `base & mask`, always empty.
+    base: Cost,
+    len: usize,
+    lowest: IndexedHeap<2>,
+    /// The items too far above the base for the ring.
+    far: IndexedHeap<2>,
+    /// First item of each bucket, a power of two of them.
+    heads: Vec<usize>,
+    /// One bit per bucket, set if it is not empty.
+    occupied: Vec<u64>,
+    keys: Vec<QueueKey>,
+    /// One past the bucket of each item, or `ABSENT`, so that zeroed buckets, which are
+    /// allocated lazily, hold no item.
+    buckets: Vec<usize>,
+    next: Vec<usize>,
+    prev: Vec<usize>,
+    scratch: Vec<usize>,
+}
+
+impl Default for BucketQueue {
+    fn default() -> Self {
+        BucketQueue {
+            base: Cost::MAX,
+            len: 0,
+            lowest: IndexedHeap::default(),
+            far: IndexedHeap::default(),
+            heads: Vec::new(),
+            occupied: Vec::new(),
+            keys: Vec::new(),
+            buckets: Vec::new(),
+            next: Vec::new(),
+            prev: Vec::new(),
+            scratch: Vec::new(),
+        }
+    }
+}
+
+impl BucketQueue {
+    /// An empty queue for the items below `items`.
+    pub fn new(items: usize) -> Self {
+        let mut queue = BucketQueue::default();
+        queue.reset(items);
+        queue
+    }
+
+    /// Number of buckets of the ring, 2^16 at most.
+    pub fn buckets(&self) -> usize {
+        self.heads.len()
+    }
+
+    fn mask(&self) -> Cost {
+        self.heads.len() as Cost - 1
+    }
+
+    fn insert(&mut self, item: usize, key: QueueKey) {
+        let span = match (key.cost / BUCKET_WIDTH).checked_sub(self.base) {
+            None | Some(0) => {
+                self.lowest.push_or_decrease(item, key);
+                return;
+            }
+            Some(span) if span >= MAX_BUCKETS as Cost => {
+                self.far.push_or_decrease(item, key);
+                return;
+            }
+            Some(span) => span,
+        };
+        if self.heads.is_empty() || span > self.mask() {
+            self.grow(span);
+        }
+        self.keys[item] = key;
+        self.link(item);
+    }
+
+    /// Moves the items of the base bucket, or lower, out of the far heap.
+    fn take_far(&mut self) {
+        while let Some((item, key)) = self.far.peek()
+            && key.cost / BUCKET_WIDTH <= self.base
+        {
+            self.far.pop();
+            self.lowest.push_or_decrease(item, key);
+        }
+    }
+
+    fn link(&mut self, item: usize) {
+        let bucket = ((self.keys[item].cost / BUCKET_WIDTH) & self.mask()) as usize;
+        let head = self.heads[bucket];
+        self.next[item] = head;
+        self.prev[item] = NONE;
+        if head != NONE {
+            self.prev[head] = item;
+        }
+        self.heads[bucket] = item;
+        self.buckets[item] = bucket + 1;
+        self.occupied[bucket / 64] |= 1 << (bucket % 64);
+    }
+
+    fn unlink(&mut self, item: usize) {
+        let bucket = self.buckets[item] - 1;
+        let (prev, next) = (self.prev[item], self.next[item]);
+        if prev == NONE {
+            self.heads[bucket] = next;
+        } else {
+            self.next[prev] = next;
+        }
+        if next != NONE {
+            self.prev[next] = prev;
+        }
+        if self.heads[bucket] == NONE {
+            self.occupied[bucket / 64] &= !(1 << (bucket % 64));
+        }
+        self.buckets[item] = ABSENT;
+    }
+
+    /// Takes the items out of the ring.
+    fn drain(&mut self) -> Vec<usize> {
+        let mut items = mem::take(&mut self.scratch);
+        for word in 0..self.occupied.len() {
+            let mut bits = self.occupied[word];
+            while bits != 0 {
+                let bucket = word * 64 + bits.trailing_zeros() as usize;
+                bits &= bits - 1;
+                items.extend(self.bucket_items(bucket));
+                self.heads[bucket] = NONE;
+            }
+            self.occupied[word] = 0;
+        }
+        for &item in &items {
+            self.buckets[item] = ABSENT;
+        }
+        items
+    }
+
+    /// Makes the ring hold the buckets up to `span` above the base.
+    fn grow(&mut self, span: Cost) {
+        let mut items = self.drain();
+        let buckets = (span + 1).next_power_of_two().max(64) as usize;
+        self.heads = vec![NONE; buckets];
+        self.occupied = vec![0; buckets / 64];
+        for &item in &items {
+            self.link(item);
+        }
+        items.clear();
+        self.scratch = items;
+    }
+
+    /// The first bucket with an item after the one of the base, wrapping around the ring.
+    fn first_occupied(&self) -> Option<usize> {
+        if self.heads.is_empty() {
+            return None;
+        }
+        let start = (self.base & self.mask()) as usize;
+        let words = self.occupied.len();
+        let first = start / 64;
+        let bits = self.occupied[first] & (u64::MAX << (start % 64));
+        if bits != 0 {
+            return Some(first * 64 + bits.trailing_zeros() as usize);
+        }
+        (1..=words).find_map(|i| {
+            let word = (first + i) % words;
+            let bits = self.occupied[word];
+            (bits != 0).then(|| word * 64 + bits.trailing_zeros() as usize)
+        })
+    }
+
+    /// The items of a bucket, following its list.
+    fn bucket_items(&self, bucket: usize) -> impl Iterator<Item = usize> + '_ {
+        let mut item = self.heads[bucket];
+        iter::from_fn(move || {
+            let current = item;
+            (current != NONE).then(|| {
+                item = self.next[current];
+                current
+            })
+        })
+    }
+}
+
+impl PriorityQueue for BucketQueue {
+    fn reset(&mut self, items: usize) {
+        let mut drained = self.drain();
+        drained.clear();
+        self.scratch = drained;
+        self.lowest.reset(items);
+        self.far.reset(items);
+        self.base = Cost::MAX;
+        self.len = 0;
+        if self.buckets.len() < items {
+            // the keys and links of an item are set when it is linked
+            self.keys = vec![QueueKey::default(); items];
+            self.buckets = vec![ABSENT; items];
+            self.next = vec![0; items];
+            self.prev = vec![0; items];
+        }
+    }
+
+    fn grow(&mut self, items: usize) {
+        self.lowest.grow(items);
+        self.far.grow(items);
+        if self.buckets.len() < items {
+            self.keys.resize(items, QueueKey::default());
+            self.buckets.resize(items, ABSENT);
+            self.next.resize(items, 0);
+            self.prev.resize(items, 0);
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn contains(&self, item: usize) -> bool {
+        self.buckets[item] != ABSENT || self.lowest.contains(item) || self.far.contains(item)
+    }
+
+    fn key(&self, item: usize) -> Option<QueueKey> {
+        if self.buckets[item] != ABSENT {
+            Some(self.keys[item])
+        } else {
+            self.lowest.key(item).or_else(|| self.far.key(item))
+        }
+    }
+
+    fn peek(&self) -> Option<(usize, QueueKey)> {
+        if !self.lowest.is_empty() {
+            return self.lowest.peek();
+        }
+        let ring = (self.first_occupied())
+            .and_then(|bucket| {
+                self.bucket_items(bucket)
+                    .min_by_key(|&item| (self.keys[item], item))
+            })
+            .map(|item| (item, self.keys[item]));
+        ring.into_iter()
+            .chain(self.far.peek())
+            .min_by_key(|&(item, key)| (key, item))
+    }
+
+    fn push_or_decrease(&mut self, item: usize, key: QueueKey) -> bool {
+        if self.lowest.contains(item) {
+            return self.lowest.push_or_decrease(item, key);
+        }
+        if self.far.contains(item) {
+            let lowered = self.far.push_or_decrease(item, key);
+            self.take_far();
+            return lowered;
+        }
+        if self.buckets[item] != ABSENT {
+            if key >= self.keys[item] {
+                return false;
+            }
+            self.unlink(item);
+        } else {
+            if self.len == 0 {
+                // the costs to come are no lower than the last popped one, not the next pushed
+                self.base = self.base.min(key.cost / BUCKET_WIDTH);
+            }
+            self.len += 1;
+        }
+        self.insert(item, key);
+        true
+    }
+
+    fn pop(&mut self) -> Option<(usize, QueueKey)> {
+        if self.lowest.is_empty() {
+            let ring = (self.first_occupied())
+                .map(|bucket| (self.keys[self.heads[bucket]].cost / BUCKET_WIDTH, bucket));
+            let far = self.far.peek().map(|(_, key)| key.cost / BUCKET_WIDTH);
+            self.base = ring.map(|(base, _)| base).into_iter().chain(far).min()?;
+            if let Some((base, bucket)) = ring
+                && base == self.base
+            {
+                while self.heads[bucket] != NONE {
+                    let item = self.heads[bucket];
+                    self.unlink(item);
+                    self.lowest.push_or_decrease(item, self.keys[item]);
+                }
+            }
+            self.take_far();
+        }
+
+        let popped = self.lowest.pop()?;
+        self.len -= 1;
+        Some(popped)
+    }
+}
@@ -0,0 +1,121 @@
+use super::{PriorityQueue, QueueKey};
+
+const ABSENT: usize = 0;
+
+/// Implicit heap of entries ordered by key then item, with `ARITY` children per entry next
+/// to each other in memory. Four children make a shallower heap than two, with cheaper
+/// insertions and key decreases for the same number of cache lines read by a removal.
+#[derive(Clone, Debug, Default)]
+pub struct IndexedHeap<const ARITY: usize> {
+    entries: Vec<(QueueKey, usize)>,
+    /// One past where each item is in `entries`, or `ABSENT`, so that zeroed slots, which
+    /// are allocated lazily, hold no item.
+    slots: Vec<usize>,
+}
+
+impl<const ARITY: usize> IndexedHeap<ARITY> {
+    /// An empty heap for the items below `items`.
+    pub fn new(items: usize) -> Self {
+        IndexedHeap {
+            entries: Vec::new(),
+            slots: vec![ABSENT; items],
+        }
+    }
+
+    fn sift_up(&mut self, mut slot: usize) {
+        let entry = self.entries[slot];
+        while slot > 0 {
+            let parent = (slot - 1) / ARITY;
+            if self.entries[parent] < entry {
+                break;
+            }
+            self.place(slot, self.entries[parent]);
+            slot = parent;
+        }
+        self.place(slot, entry);
+    }
+
+    fn sift_down(&mut self, mut slot: usize) {
+        let entry = self.entries[slot];
+        loop {
+            let first = slot * ARITY + 1;
+            let last = (first + ARITY).min(self.entries.len());
+            let Some(child) = (first..last).min_by_key(|&child| self.entries[child]) else {
+                break;
+            };
+            if self.entries[child] > entry {
+                break;
+            }
+            self.place(slot, self.entries[child]);
+            slot = child;
+        }
+        self.place(slot, entry);
+    }
+
+    fn place(&mut self, slot: usize, entry: (QueueKey, usize)) {
+        self.entries[slot] = entry;
+        self.slots[entry.1] = slot + 1;
+    }
+}
+
+impl<const ARITY: usize> PriorityQueue for IndexedHeap<ARITY> {
+    fn reset(&mut self, items: usize) {
+        for &(_, item) in &self.entries {
+            self.slots[item] = ABSENT;
+        }
+        self.entries.clear();
+        if self.slots.len() < items {
+            self.slots = vec![ABSENT; items];
+        }
+    }
+
+    fn grow(&mut self, items: usize) {
+        if self.slots.len() < items {
+            self.slots.resize(items, ABSENT);
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn contains(&self, item: usize) -> bool {
+        self.slots[item] != ABSENT
+    }
+
+    fn key(&self, item: usize) -> Option<QueueKey> {
+        let slot = self.slots[item];
+        (slot != ABSENT).then(|| self.entries[slot - 1].0)
+    }
+
+    fn peek(&self) -> Option<(usize, QueueKey)> {
+        self.entries.first().map(|&(key, item)| (item, key))
+    }
+
+    fn push_or_decrease(&mut self, item: usize, key: QueueKey) -> bool {
+        let slot = match self.slots[item] {
+            ABSENT => {
+                self.entries.push((key, item));
+                self.entries.len() - 1
+            }
+            slot if key < self.entries[slot - 1].0 => {
+                self.entries[slot - 1].0 = key;
+                slot - 1
+            }
+            _ => return false,
+        };
+        self.sift_up(slot);
+        true
+    }
+
+    fn pop(&mut self) -> Option<(usize, QueueKey)> {
+        let last = self.entries.len().checked_sub(1)?;
+        self.entries.swap(0, last);
+        let (key, item) = self.entries.pop().expect("not empty");
+        self.slots[item] = ABSENT;
+        if !self.entries.is_empty() {
+            self.sift_down(0);
+        }
+        Some((item, key))
+    }
+}
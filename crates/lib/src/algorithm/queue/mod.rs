@@ -0,0 +1,93 @@
+mod bucket;
+mod heap;
+mod pairing;
+mod radix;
+
+use std::fmt::Debug;
+
+use crate::cost::Cost;
+
+pub use bucket::BucketQueue;
+pub use heap::IndexedHeap;
+pub use pairing::PairingHeap;
+pub use radix::RadixHeap;
+
+/// Key of an item of a [`PriorityQueue`]: its cost, then a tie-breaker between equal costs,
+/// both lowest first.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct QueueKey {
+    pub cost: Cost,
+    pub tie: u64,
+}
+
+impl QueueKey {
+    pub fn new(cost: Cost, tie: u64) -> Self {
+        QueueKey { cost, tie }
+    }
+}
+
+/// Min-queue of items identified by dense indices, each at most once, whose keys can be
+/// lowered in place. The open list of a [`Search`](super::Search).
+///
+/// Items of equal keys are popped lowest first, so that every queue pops the same items in
+/// the same order.
+pub trait PriorityQueue: Debug + Send {
+    /// Empties the queue and makes room for the items below `items`. Only the items that
+    /// were in the queue are cleared, and new room is allocated zeroed, so resetting a queue
+    /// costs as much as emptying it whatever the number of items.
+    fn reset(&mut self, items: usize);
+
+    /// Makes room for the items below `items`, keeping the ones in the queue.
+    fn grow(&mut self, items: usize);
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn contains(&self, item: usize) -> bool;
+
+    fn key(&self, item: usize) -> Option<QueueKey>;
+
+    /// The item the next [`PriorityQueue::pop`] returns, and its key.
+    fn peek(&self) -> Option<(usize, QueueKey)>;
+
+    /// Inserts `item`, or lowers its key if it is already in the queue with a higher one.
+    /// Returns whether the queue changed.
+    fn push_or_decrease(&mut self, item: usize, key: QueueKey) -> bool;
+
+    /// Removes the item with the lowest key, returning it with its key.
+    fn pop(&mut self) -> Option<(usize, QueueKey)>;
+}
+
+/// The [`PriorityQueue`] implementations, to choose one at run time.
+///
+/// The radix heap and the bucket queue are monotone: they are fastest when costs are no
+/// lower than the last one popped, as with the A* keys of a consistent heuristic. They keep
+/// the items of the lowest cost in a heap, which also takes the lower costs of weighted and
+/// greedy searches, so their order is exact either way.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum QueueKind {
+    /// [`IndexedHeap`] with two children per entry.
+    Binary,
+    /// [`IndexedHeap`] with four children per entry.
+    #[default]
+    Quaternary,
+    Pairing,
+    Radix,
+    Bucket,
+}
+
+impl QueueKind {
+    /// An empty queue of this kind.
+    pub fn build(self) -> Box<dyn PriorityQueue> {
+        match self {
+            QueueKind::Binary => Box::new(IndexedHeap::<2>::default()),
+            QueueKind::Quaternary => Box::new(IndexedHeap::<4>::default()),
+            QueueKind::Pairing => Box::new(PairingHeap::default()),
+            QueueKind::Radix => Box::new(RadixHeap::default()),
+            QueueKind::Bucket => Box::new(BucketQueue::default()),
+        }
+    }
+}
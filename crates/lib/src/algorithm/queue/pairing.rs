@@ -0,0 +1,188 @@
+use std::mem;
+
+use super::{PriorityQueue, QueueKey};
+
+const NONE: usize = usize::MAX;
+
+/// Pairing heap: a tree of items linked in place, where insertions and key decreases meld
+/// a single item with the root in constant time and removals pair up the children of the
+/// root.
+#[derive(Clone, Debug, Default)]
+pub struct PairingHeap {
+    root: usize,
+    len: usize,
+    keys: Vec<QueueKey>,
+    present: Vec<bool>,
+    /// First child of each item.
+    child: Vec<usize>,
+    /// Next sibling of each item.
+    sibling: Vec<usize>,
+    /// Previous sibling of each item, or its parent for a first child.
+    prev: Vec<usize>,
+    scratch: Vec<usize>,
+}
+
+impl PairingHeap {
+    /// An empty heap for the items below `items`.
+    pub fn new(items: usize) -> Self {
+        let mut heap = PairingHeap::default();
+        heap.reset(items);
+        heap
+    }
+
+    /// Makes the root with the higher key, or item on equal keys, the first child of the
+    /// other one, returning the root of the result.
+    fn meld(&mut self, a: usize, b: usize) -> usize {
+        let (root, child) = if (self.keys[b], b) < (self.keys[a], a) {
+            (b, a)
+        } else {
+            (a, b)
+        };
+        let first = self.child[root];
+        self.sibling[child] = first;
+        if first != NONE {
+            self.prev[first] = child;
+        }
+        self.prev[child] = root;
+        self.child[root] = child;
+        root
+    }
+
+    /// Detaches the subtree of `item` from its parent.
+    fn cut(&mut self, item: usize) {
+        let (prev, next) = (self.prev[item], self.sibling[item]);
+        if self.child[prev] == item {
+            self.child[prev] = next;
+        } else {
+            self.sibling[prev] = next;
+        }
+        if next != NONE {
+            self.prev[next] = prev;
+        }
+        self.prev[item] = NONE;
+        self.sibling[item] = NONE;
+    }
+
+    /// Melds the children of a removed root in pairs from the left, then the pairs from
+    /// the right.
+    fn merge_children(&mut self, first: usize) -> usize {
+        let mut roots = mem::take(&mut self.scratch);
+        let mut next = first;
+        while next != NONE {
+            let a = next;
+            let b = self.sibling[a];
+            next = if b == NONE { NONE } else { self.sibling[b] };
+            self.detach(a);
+            roots.push(if b == NONE {
+                a
+            } else {
+                self.detach(b);
+                self.meld(a, b)
+            });
+        }
+
+        let mut root = roots.pop().unwrap_or(NONE);
+        while let Some(other) = roots.pop() {
+            root = self.meld(other, root);
+        }
+        self.scratch = roots;
+        root
+    }
+
+    fn detach(&mut self, item: usize) {
+        self.prev[item] = NONE;
+        self.sibling[item] = NONE;
+    }
+}
+
+impl PriorityQueue for PairingHeap {
+    fn reset(&mut self, items: usize) {
+        let mut stack = mem::take(&mut self.scratch);
+        if self.root != NONE && self.len > 0 {
+            stack.push(self.root);
+        }
+        while let Some(item) = stack.pop() {
+            self.present[item] = false;
+            for next in [self.child[item], self.sibling[item]] {
+                if next != NONE {
+                    stack.push(next);
+                }
+            }
+        }
+        self.scratch = stack;
+
+        self.root = NONE;
+        self.len = 0;
+        if self.present.len() < items {
+            // the key and links of an item are set when it is pushed
+            self.keys = vec![QueueKey::default(); items];
+            self.present = vec![false; items];
+            self.child = vec![0; items];
+            self.sibling = vec![0; items];
+            self.prev = vec![0; items];
+        }
+    }
+
+    fn grow(&mut self, items: usize) {
+        if self.present.len() < items {
+            self.keys.resize(items, QueueKey::default());
+            self.present.resize(items, false);
+            self.child.resize(items, 0);
+            self.sibling.resize(items, 0);
+            self.prev.resize(items, 0);
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn contains(&self, item: usize) -> bool {
+        self.present[item]
+    }
+
+    fn key(&self, item: usize) -> Option<QueueKey> {
+        self.contains(item).then(|| self.keys[item])
+    }
+
+    fn peek(&self) -> Option<(usize, QueueKey)> {
+        (self.len > 0).then(|| (self.root, self.keys[self.root]))
+    }
+
+    fn push_or_decrease(&mut self, item: usize, key: QueueKey) -> bool {
+        if self.present[item] {
+            if key >= self.keys[item] {
+                return false;
+            }
+            self.keys[item] = key;
+            if item == self.root {
+                return true;
+            }
+            self.cut(item);
+        } else {
+            self.keys[item] = key;
+            self.present[item] = true;
+            self.child[item] = NONE;
+            self.detach(item);
+            self.len += 1;
+            if self.len == 1 {
+                self.root = item;
+                return true;
+            }
+        }
+        self.root = self.meld(self.root, item);
+        true
+    }
+
+    fn pop(&mut self) -> Option<(usize, QueueKey)> {
+        if self.len == 0 {
+            return None;
+        }
+        let root = self.root;
+        self.present[root] = false;
+        self.len -= 1;
+        self.root = self.merge_children(self.child[root]);
+        self.child[root] = NONE;
+        Some((root, self.keys[root]))
+    }
+}
@@ -0,0 +1,164 @@
+use std::mem;
+
+use crate::cost::Cost;
+
+use super::{IndexedHeap, PriorityQueue, QueueKey};
+
+/// One bucket per bit where a cost can first differ from the last popped one.
+const BUCKETS: usize = Cost::BITS as usize;
+
+const ABSENT: u8 = 0;
+
+/// Monotone radix heap: each cost above the last popped one is in the bucket of the highest
+/// bit where they differ. Removals only sort the first non-empty bucket, spreading it over
+/// the lower ones, so each item moves at most once per bit of its cost.
+///
+/// The items of the lowest cost, or lower, are in a binary heap that orders their ties.
+#[derive(Clone, Debug)]
+pub struct RadixHeap {
+    /// The last cost popped, or the lowest pushed since the heap was emptied.
+    last: Cost,
+    len: usize,
+    lowest: IndexedHeap<2>,
+    buckets: Vec<Vec<(QueueKey, usize)>>,
+    /// One past the bucket of each item, or `ABSENT`, so that zeroed slots, which are
+    /// allocated lazily, hold no item.
+    slots: Vec<u8>,
+    /// Index of each item in its bucket.
+    indices: Vec<usize>,
+}
+
+impl Default for RadixHeap {
+    fn default() -> Self {
+        RadixHeap {
+            last: Cost::MAX,
+            len: 0,
+            lowest: IndexedHeap::default(),
+            buckets: vec![Vec::new(); BUCKETS],
+            slots: Vec::new(),
+            indices: Vec::new(),
+        }
+    }
+}
+
+impl RadixHeap {
+    /// An empty heap for the items below `items`.
+    pub fn new(items: usize) -> Self {
+        let mut heap = RadixHeap::default();
+        heap.reset(items);
+        heap
+    }
+
+    fn insert(&mut self, item: usize, key: QueueKey) {
+        if key.cost <= self.last {
+            self.lowest.push_or_decrease(item, key);
+            return;
+        }
+        let bucket = (Cost::BITS - 1 - (key.cost ^ self.last).leading_zeros()) as usize;
+        self.slots[item] = bucket as u8 + 1;
+        self.indices[item] = self.buckets[bucket].len();
+        self.buckets[bucket].push((key, item));
+    }
+
+    fn remove(&mut self, item: usize) {
+        let index = self.indices[item];
+        let bucket = &mut self.buckets[self.slots[item] as usize - 1];
+        bucket.swap_remove(index);
+        if let Some(&(_, moved)) = bucket.get(index) {
+            self.indices[moved] = index;
+        }
+        self.slots[item] = ABSENT;
+    }
+
+    fn first_bucket(&self) -> Option<usize> {
+        self.buckets.iter().position(|b| !b.is_empty())
+    }
+}
+
+impl PriorityQueue for RadixHeap {
+    fn reset(&mut self, items: usize) {
+        for bucket in &mut self.buckets {
+            for &(_, item) in bucket.iter() {
+                self.slots[item] = ABSENT;
+            }
+            bucket.clear();
+        }
+        self.lowest.reset(items);
+        self.last = Cost::MAX;
+        self.len = 0;
+        if self.slots.len() < items {
+            self.slots = vec![ABSENT; items];
+            self.indices = vec![0; items];
+        }
+    }
+
+    fn grow(&mut self, items: usize) {
+        self.lowest.grow(items);
+        if self.slots.len() < items {
+            self.slots.resize(items, ABSENT);
+            self.indices.resize(items, 0);
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn contains(&self, item: usize) -> bool {
+        self.slots[item] != ABSENT || self.lowest.contains(item)
+    }
+
+    fn key(&self, item: usize) -> Option<QueueKey> {
+        match self.slots[item] {
+            ABSENT => self.lowest.key(item),
+            bucket => Some(self.buckets[bucket as usize - 1][self.indices[item]].0),
+        }
+    }
+
+    fn peek(&self) -> Option<(usize, QueueKey)> {
+        if !self.lowest.is_empty() {
+            return self.lowest.peek();
+        }
+        let &(key, item) = self.buckets[self.first_bucket()?].iter().min()?;
+        Some((item, key))
+    }
+
+    fn push_or_decrease(&mut self, item: usize, key: QueueKey) -> bool {
+        if self.lowest.contains(item) {
+            return self.lowest.push_or_decrease(item, key);
+        }
+        match self.key(item) {
+            Some(current) if key >= current => return false,
+            Some(_) => self.remove(item),
+            None => {
+                if self.len == 0 {
+                    // the costs to come are no lower than the last popped one, not the next
+                    // pushed
+                    self.last = self.last.min(key.cost);
+                }
+                self.len += 1;
+            }
+        }
+        self.insert(item, key);
+        true
+    }
+
+    fn pop(&mut self) -> Option<(usize, QueueKey)> {
+        if self.lowest.is_empty() {
+            let first = self.first_bucket()?;
+            let spread = mem::take(&mut self.buckets[first]);
+            self.last = spread.iter().map(|&(key, _)| key.cost).min()?;
+            for &(key, item) in &spread {
+                self.slots[item] = ABSENT;
+                self.insert(item, key);
+            }
+            // keep the allocation of the bucket
+            self.buckets[first] = spread;
+            self.buckets[first].clear();
+        }
+
+        let popped = self.lowest.pop()?;
+        self.len -= 1;
+        Some(popped)
+    }
+}
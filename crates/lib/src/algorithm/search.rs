@@ -0,0 +1,449 @@
+use std::{cmp::max, collections::HashMap};
+
+use crate::{
+    channel::ChannelSender,
+    cost::{Cost, scale_cost},
+    generator::{ChildrenGenerator, MazePath, NodeIndex},
+    heuristics::MazeHeuristic,
+    position::Position,
+};
+
+use super::{Child, Info, Message, PriorityQueue, QueueKey, QueueKind, SearchLimits, StopReason};
+
+const NO_PARENT: usize = usize::MAX;
+
+/// Outcome of [`Search::step`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Step {
+    /// The node was expanded, its children are in the open list.
+    Expanded(Position),
+    /// The search is over, further steps do nothing.
+    Finished(StopReason),
+}
+
+/// Order of the nodes of equal priority in the open list of a [`Search`]. Nodes still tied
+/// are expanded in row-major order, so that the same search always expands the same nodes
+/// whatever its [`QueueKind`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TieBreak {
+    /// The node farthest from the start first, usually the closest to the goal.
+    #[default]
+    HigherG,
+    /// The node with the lowest heuristic first.
+    LowerH,
+    /// The node reached or improved last first.
+    Lifo,
+    /// The node reached or improved first first.
+    Fifo,
+    /// The node closest to the line from the start to the goal first, measured by the cross
+    /// product of their offsets to the goal.
+    CrossProduct,
+}
+
+/// State of a [`Search`] indexed by node, kept between searches so that the next one does
+/// not allocate it again.
+///
+/// A node holds a value only if its stamp is the one of the current search, so starting a
+/// search does not clear the vectors either.
+///
+/// With a [`NodeIndex`], the vectors hold every node of the maze: 28 bytes each, and from 8
+/// to 56 more in the open list depending on its [`QueueKind`]. They are allocated zeroed, so
+/// only the pages of the nodes a search reaches are actually committed.
+#[derive(Debug)]
+pub struct SearchBuffers {
+    queue: QueueKind,
+    generation: u32,
+    stamps: Vec<u32>,
+    depth: Vec<Cost>,
+    heuristic: Vec<Cost>,
+    parents: Vec<usize>,
+    /// Indices of the nodes reached, for generators without a [`NodeIndex`].
+    numbering: HashMap<Position, usize>,
+    numbered: Vec<Position>,
+    open: Box<dyn PriorityQueue>,
+}
+
+impl Default for SearchBuffers {
+    fn default() -> Self {
+        SearchBuffers::new(QueueKind::default())
+    }
+}
+
+impl SearchBuffers {
+    /// Empty buffers keeping the open list in a queue of kind `queue`.
+    pub fn new(queue: QueueKind) -> Self {
+        SearchBuffers {
+            queue,
+            generation: 0,
+            stamps: Vec::new(),
+            depth: Vec::new(),
+            heuristic: Vec::new(),
+            parents: Vec::new(),
+            numbering: HashMap::new(),
+            numbered: Vec::new(),
+            open: queue.build(),
+        }
+    }
+
+    /// Kind of the queue holding the open list.
+    pub fn queue(&self) -> QueueKind {
+        self.queue
+    }
+
+    fn prepare(&mut self, nodes: usize) {
+        if self.stamps.len() < nodes {
+            // zeroed vectors are allocated lazily, as is the room of the open list, so a
+            // search only touches the memory of the nodes it reaches
+            self.stamps = vec![0; nodes];
+            self.depth = vec![0; nodes];
+            self.heuristic = vec![0; nodes];
+            self.parents = vec![0; nodes];
+        }
+        self.open.reset(nodes);
+        self.numbering.clear();
+        self.numbered.clear();
+
+        self.generation = self.generation.wrapping_add(1);
+        if self.generation == 0 {
+            self.stamps.fill(0);
+            self.generation = 1;
+        }
+    }
+
+    /// Makes room for the nodes below `nodes`, keeping the state of the search.
+    fn grow(&mut self, nodes: usize) {
+        if self.stamps.len() < nodes {
+            self.stamps.resize(nodes, 0);
+            self.depth.resize(nodes, 0);
+            self.heuristic.resize(nodes, 0);
+            self.parents.resize(nodes, 0);
+        }
+        self.open.grow(nodes);
+    }
+
+    /// Index of `node` in the order the search reached the nodes, giving it the next one if
+    /// it is new.
+    fn number(&mut self, node: Position) -> usize {
+        let next = self.numbered.len();
+        let cell = *self.numbering.entry(node).or_insert(next);
+        if cell == next {
+            self.numbered.push(node);
+            self.grow(next + 1);
+        }
+        cell
+    }
+
+    fn is_seen(&self, cell: usize) -> bool {
+        self.stamps[cell] == self.generation
+    }
+}
+
+/// A best-first search advanced one expansion at a time, owning its open and closed sets so
+/// that it can be paused, inspected and resumed from the calling thread.
+///
+/// Nodes are expanded in order of `depth_weight * g + heuristic_weight * h`, which is A*
+/// for weights of 1, then of their [`TieBreak`]. Each node is in the open list at most once,
+/// with its key lowered when a cheaper path to it is found, and is never reopened once
+/// expanded.
+pub struct Search<'a, G: ChildrenGenerator + ?Sized, C: ChannelSender<Message>> {
+    start: Position,
+    goal: Position,
+    heuristic: &'a dyn MazeHeuristic,
+    generator: &'a G,
+    index: Option<&'a dyn NodeIndex>,
+    channel: C,
+    depth_weight: f64,
+    heuristic_weight: f64,
+    tie_break: TieBreak,
+    /// Pushes to the open list so far, ordering [`TieBreak::Lifo`] and [`TieBreak::Fifo`].
+    pushes: u64,
+    buffers: SearchBuffers,
+    info: Info,
+    finished: Option<StopReason>,
+}
+
+impl<'a, G: ChildrenGenerator + ?Sized, C: ChannelSender<Message>> Search<'a, G, C> {
+    /// An A* search from `start` to `goal`, with only `start` in the open list.
+    pub fn new(
+        start: Position,
+        goal: Position,
+        heuristic: &'a dyn MazeHeuristic,
+        generator: &'a G,
+        channel: C,
+    ) -> Self {
+        Search::with_weights(start, goal, heuristic, generator, channel, 1.0, 1.0)
+    }
+
+    pub fn with_weights(
+        start: Position,
+        goal: Position,
+        heuristic: &'a dyn MazeHeuristic,
+        generator: &'a G,
+        channel: C,
+        depth_weight: f64,
+        heuristic_weight: f64,
+    ) -> Self {
+        Search::with_buffers(
+            SearchBuffers::default(),
+            start,
+            goal,
+            heuristic,
+            generator,
+            channel,
+            depth_weight,
+            heuristic_weight,
+        )
+    }
+
+    /// Like [`Search::with_weights`], reusing the buffers of a previous search, given back
+    /// by [`Search::into_parts`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn with_buffers(
+        mut buffers: SearchBuffers,
+        start: Position,
+        goal: Position,
+        heuristic: &'a dyn MazeHeuristic,
+        generator: &'a G,
+        channel: C,
+        depth_weight: f64,
+        heuristic_weight: f64,
+    ) -> Self {
+        let index = generator.node_index();
+        buffers.prepare(index.map_or(0, |index| index.node_count()));
+
+        let mut search = Search {
+            start,
+            goal,
+            heuristic,
+            generator,
+            index,
+            channel,
+            depth_weight,
+            heuristic_weight,
+            tie_break: TieBreak::default(),
+            pushes: 0,
+            buffers,
+            info: Info::default(),
+            finished: None,
+        };
+        let h = search.weighted_heuristic(start);
+        let cell = search.cell(start);
+        search.reach(cell, NO_PARENT, 0, h);
+        search.push(cell, start);
+        search
+    }
+
+    /// Sets how the nodes of equal priority are ordered. Only the start is in the open list
+    /// until the first step, so the order is the same as if the search had started with it.
+    pub fn with_tie_break(self, tie_break: TieBreak) -> Self {
+        Search { tie_break, ..self }
+    }
+
+    /// Expands the next node of the open list, ignoring any [`SearchLimits`].
+    pub fn step(&mut self) -> Step {
+        if let Some(stop) = self.finished {
+            return Step::Finished(stop);
+        }
+        let Some((current, _)) = self.buffers.open.pop() else {
+            return self.finish(StopReason::Exhausted);
+        };
+
+        self.info.nodes += 1;
+        self.info.max_length = max(self.info.max_length, self.buffers.open.len());
+
+        let current_node = self.position(current);
+        if current_node == self.goal {
+            let path = self.build_path(current);
+            self.info
+                .path
+                .replace(self.generator.reconstruct_path(&path));
+            return self.finish(StopReason::Found);
+        }
+
+        let parent = self.buffers.parents[current];
+        let parent = (parent != NO_PARENT).then(|| self.position(parent));
+        let current_depth = self.buffers.depth[current];
+        for generated in self.generator.generate_children(current_node, parent) {
+            let Child {
+                node: successor,
+                weight,
+            } = generated;
+
+            let cell = self.cell(successor);
+            let seen = self.buffers.is_seen(cell);
+            if seen && !self.buffers.open.contains(cell) {
+                continue;
+            }
+
+            let successor_depth = current_depth + weight;
+            if seen && successor_depth >= self.buffers.depth[cell] {
+                continue;
+            }
+
+            let h = if seen {
+                self.buffers.heuristic[cell]
+            } else {
+                self.weighted_heuristic(successor)
+            };
+            self.reach(cell, current, successor_depth, h);
+
+            if self
+                .channel
+                .send(Message::Enqueued(successor, successor_depth))
+                .is_err()
+            {
+                return self.finish(StopReason::Disconnected);
+            }
+
+            self.push(cell, successor);
+        }
+
+        Step::Expanded(current_node)
+    }
+
+    /// Steps until the search is over or one of `limits` is reached, returning why it
+    /// stopped. The limits apply to the whole search: once paused on one, it can be resumed
+    /// with looser limits.
+    pub fn run_until(&mut self, limits: &SearchLimits) -> StopReason {
+        loop {
+            if self.finished.is_none()
+                && !self.buffers.open.is_empty()
+                && let Some(stop) = limits.check(self.info.nodes, self.buffers.open.len())
+            {
+                self.info.stop = stop;
+                return stop;
+            }
+
+            if let Step::Finished(stop) = self.step() {
+                return stop;
+            }
+        }
+    }
+
+    /// Why the search is over, or `None` while nodes can still be expanded.
+    pub fn finished(&self) -> Option<StopReason> {
+        self.finished
+    }
+
+    /// The node the next step will expand.
+    pub fn peek(&self) -> Option<Position> {
+        self.buffers
+            .open
+            .peek()
+            .map(|(cell, _)| self.position(cell))
+    }
+
+    /// Cost of the best path found so far from the start to `node`, if any.
+    pub fn g(&self, node: Position) -> Option<Cost> {
+        self.seen_cell(node).map(|cell| self.buffers.depth[cell])
+    }
+
+    /// Priority of `node` in the open list, if a path to it was found.
+    pub fn f(&self, node: Position) -> Option<Cost> {
+        self.seen_cell(node).map(|cell| {
+            scale_cost(self.buffers.depth[cell], self.depth_weight) + self.buffers.heuristic[cell]
+        })
+    }
+
+    pub fn is_closed(&self, node: Position) -> bool {
+        self.seen_cell(node)
+            .is_some_and(|cell| !self.buffers.open.contains(cell))
+    }
+
+    /// Number of nodes in the open list.
+    pub fn open_len(&self) -> usize {
+        self.buffers.open.len()
+    }
+
+    /// Statistics so far, and the path once found.
+    pub fn info(&self) -> &Info {
+        &self.info
+    }
+
+    pub fn into_info(self) -> Info {
+        self.into_parts().0
+    }
+
+    /// The statistics and the buffers, to be reused by another search.
+    pub fn into_parts(self) -> (Info, SearchBuffers) {
+        (self.info, self.buffers)
+    }
+
+    fn weighted_heuristic(&self, node: Position) -> Cost {
+        match self.heuristic_weight {
+            0.0 => 0,
+            w => scale_cost(self.heuristic.compute_heuristic(node), w),
+        }
+    }
+
+    /// Puts `cell` in the open list with the priority of its depth and heuristic.
+    fn push(&mut self, cell: usize, node: Position) {
+        let (depth, heuristic) = (self.buffers.depth[cell], self.buffers.heuristic[cell]);
+        let tie = match self.tie_break {
+            TieBreak::HigherG => u64::MAX - depth,
+            TieBreak::LowerH => heuristic,
+            TieBreak::Lifo => u64::MAX - self.pushes,
+            TieBreak::Fifo => self.pushes,
+            TieBreak::CrossProduct => {
+                let (a, b) = (node - self.goal, self.start - self.goal);
+                (a.x as i64 * b.y as i64 - b.x as i64 * a.y as i64).unsigned_abs()
+            }
+        };
+        self.pushes += 1;
+        let priority = scale_cost(depth, self.depth_weight) + heuristic;
+        self.buffers
+            .open
+            .push_or_decrease(cell, QueueKey::new(priority, tie));
+    }
+
+    fn reach(&mut self, cell: usize, parent: usize, depth: Cost, heuristic: Cost) {
+        let buffers = &mut self.buffers;
+        buffers.stamps[cell] = buffers.generation;
+        buffers.depth[cell] = depth;
+        buffers.heuristic[cell] = heuristic;
+        buffers.parents[cell] = parent;
+    }
+
+    /// Index of `node` in the buffers, numbering it if the generator does not.
+    fn cell(&mut self, node: Position) -> usize {
+        match self.index {
+            Some(index) => index
+                .index_of(node)
+                .expect("the generator numbers its nodes"),
+            None => self.buffers.number(node),
+        }
+    }
+
+    fn position(&self, cell: usize) -> Position {
+        match self.index {
+            Some(index) => index.node_at(cell),
+            None => self.buffers.numbered[cell],
+        }
+    }
+
+    /// The index of `node` if the search reached it.
+    fn seen_cell(&self, node: Position) -> Option<usize> {
+        match self.index {
+            Some(index) => index.index_of(node),
+            None => self.buffers.numbering.get(&node).copied(),
+        }
+        .filter(|&cell| self.buffers.is_seen(cell))
+    }
+
+    /// Walks the parents back from `cell`, returning the nodes from the start to it.
+    fn build_path(&self, mut cell: usize) -> MazePath {
+        let mut path = vec![self.position(cell)];
+        while self.buffers.parents[cell] != NO_PARENT {
+            cell = self.buffers.parents[cell];
+            path.push(self.position(cell));
+        }
+        path.reverse();
+        path
+    }
+
+    fn finish(&mut self, stop: StopReason) -> Step {
+        self.finished = Some(stop);
+        self.info.stop = stop;
+        Step::Finished(stop)
+    }
+}
@@ -0,0 +1,301 @@
+use std::{
+    cmp::max,
+    collections::{BTreeSet, HashMap},
+    mem,
+};
+
+use crate::{
+    channel::ChannelSender, cost::Cost, generator::ChildrenGenerator, heuristics::MazeHeuristic,
+    position::Position,
+};
+
+use super::{Child, Info, Message, SearchAlgorithm, SearchLimits, StopReason, build_path};
+
+/// Simplified memory-bounded A*: behaves like A* until `max_nodes` nodes are in memory,
+/// then forgets the shallowest leaf with the highest `f`, remembering its value in the
+/// parent so that the subtree is regenerated only when it becomes the most promising again,
+/// and never below the value it had when it was forgotten. Room is made before each child is
+/// stored, so the cap is never exceeded.
+///
+/// The path is optimal as long as it fits in memory. Nodes that would end a path longer than
+/// the cap can never be part of a stored path, so they are given an infinite `f`, `Cost::MAX`.
+pub struct SmaStar {
+    pub max_nodes: usize,
+}
+
+impl SmaStar {
+    pub fn new(max_nodes: usize) -> Self {
+        SmaStar { max_nodes }
+    }
+}
+
+/// Open list key: lowest `f` first, deepest first among equal `f`.
+type OpenKey = (Cost, u32, (i32, i32));
+
+struct Node {
+    depth: Cost,
+    f: Cost,
+    level: u32,
+    parent: Option<Position>,
+    children: Vec<Position>,
+    forgotten: Vec<(Position, Cost)>,
+    open: Option<OpenKey>,
+}
+
+impl Node {
+    /// The best `f` among the forgotten children, infinite if there are none.
+    fn forgotten_f(&self) -> Cost {
+        self.forgotten
+            .iter()
+            .map(|&(_, f)| f)
+            .min()
+            .unwrap_or(Cost::MAX)
+    }
+}
+
+struct State<'a> {
+    nodes: HashMap<Position, Node>,
+    open: BTreeSet<OpenKey>,
+    heuristic: &'a dyn MazeHeuristic,
+    goal: Position,
+    max_nodes: usize,
+}
+
+impl SearchAlgorithm for SmaStar {
+    fn search_with_limits<G: ChildrenGenerator + ?Sized, C: ChannelSender<Message>>(
+        &self,
+        start: Position,
+        goal: Position,
+        heuristic: &dyn MazeHeuristic,
+        generator: &G,
+        channel: C,
+        limits: &SearchLimits,
+    ) -> Info {
+        let mut info = Info::default();
+        let mut state = State {
+            nodes: HashMap::new(),
+            open: BTreeSet::new(),
+            heuristic,
+            goal,
+            max_nodes: self.max_nodes.max(2),
+        };
+
+        state.nodes.insert(
+            start,
+            Node {
+                depth: 0,
+                f: heuristic.compute_heuristic(start),
+                level: 0,
+                parent: None,
+                children: Vec::new(),
+                forgotten: Vec::new(),
+                open: None,
+            },
+        );
+        state.reopen(start);
+
+        while let Some(&(f, _, current)) = state.open.first() {
+            if f == Cost::MAX {
+                break;
+            }
+
+            let current = Position::from(current);
+            if let Some(stop) = limits.check(info.nodes, state.open.len()) {
+                info.stop = stop;
+                return info;
+            }
+            info.nodes += 1;
+
+            if current == goal {
+                let parents = state
+                    .nodes
+                    .iter()
+                    .filter_map(|(&p, n)| n.parent.map(|parent| (p, parent)))
+                    .collect();
+                let path = build_path(&parents, start, goal);
+                info.path.replace(generator.reconstruct_path(&path));
+                info.stop = StopReason::Found;
+                return info;
+            }
+
+            state.close(current);
+            let node = state.nodes.get_mut(&current).unwrap();
+            node.f = node.f.max(f);
+            let forgotten = mem::take(&mut node.forgotten);
+
+            let parent = node.parent;
+            for Child {
+                node: successor,
+                weight,
+            } in generator.generate_children(current, parent)
+            {
+                let backed_up = forgotten
+                    .iter()
+                    .find(|&&(p, _)| p == successor)
+                    .map_or(0, |&(_, f)| f);
+                if let Some(depth) = state.insert(current, successor, weight, backed_up) {
+                    if channel.send(Message::Enqueued(successor, depth)).is_err() {
+                        info.stop = StopReason::Disconnected;
+                        return info;
+                    }
+                    info.max_length = max(info.max_length, state.nodes.len());
+                }
+            }
+
+            state.refresh(current);
+        }
+
+        info
+    }
+}
+
+impl State<'_> {
+    /// Adds `successor` as a child of `current`, returning its depth if it was stored.
+    /// `backed_up` is the value the successor had when it was last forgotten, if ever.
+    fn insert(
+        &mut self,
+        current: Position,
+        successor: Position,
+        weight: Cost,
+        backed_up: Cost,
+    ) -> Option<Cost> {
+        let parent = &self.nodes[&current];
+        let depth = parent.depth + weight;
+        let level = parent.level + 1;
+        let parent_f = parent.f;
+
+        if let Some(existing) = self.nodes.get(&successor) {
+            if existing.depth <= depth || existing.parent == Some(current) {
+                return None;
+            }
+            // the subtree hangs from the costlier path, it is regenerated from the cheaper one
+            let old_parent = self.remove_subtree(successor);
+            self.refresh(old_parent);
+        }
+
+        // a path through a node at `level` holds at least `level + 1` nodes, one more unless
+        // the node is the goal
+        let nodes = level as usize + 1 + usize::from(successor != self.goal);
+        let f = if nodes > self.max_nodes {
+            Cost::MAX
+        } else {
+            parent_f
+                .max(backed_up)
+                .max(depth + self.heuristic.compute_heuristic(successor))
+        };
+
+        if !self.make_room(current, (f, u32::MAX - level, successor.into())) {
+            let node = self.nodes.get_mut(&current).unwrap();
+            node.forgotten.push((successor, f));
+            return None;
+        }
+
+        self.nodes.insert(
+            successor,
+            Node {
+                depth,
+                f,
+                level,
+                parent: Some(current),
+                children: Vec::new(),
+                forgotten: Vec::new(),
+                open: None,
+            },
+        );
+        self.nodes
+            .get_mut(&current)
+            .unwrap()
+            .children
+            .push(successor);
+        self.reopen(successor);
+
+        Some(depth)
+    }
+
+    /// Makes room for a new child of `current` with open list key `key` once memory is full,
+    /// by forgetting the worst leaf other than `current`. Returns false if that leaf is not
+    /// worse than the new child, which must be forgotten instead.
+    fn make_room(&mut self, current: Position, key: OpenKey) -> bool {
+        if self.nodes.len() < self.max_nodes {
+            return true;
+        }
+
+        let worst = self.open.iter().rev().find(|&&(_, _, p)| {
+            let p = Position::from(p);
+            let node = &self.nodes[&p];
+            p != current && node.parent.is_some() && node.children.is_empty()
+        });
+
+        let Some(&(_, _, worst)) = worst.filter(|&&w| w > key) else {
+            return false;
+        };
+        let worst = Position::from(worst);
+
+        let f = self.nodes[&worst].f;
+        let parent = self.remove(worst);
+        let node = self.nodes.get_mut(&parent).unwrap();
+        node.forgotten.push((worst, f));
+        if parent != current {
+            self.refresh(parent);
+        }
+        true
+    }
+
+    /// Removes a leaf from memory, returning its parent.
+    fn remove(&mut self, pos: Position) -> Position {
+        self.close(pos);
+        let node = self.nodes.remove(&pos).unwrap();
+        let parent = node.parent.unwrap();
+        self.nodes
+            .get_mut(&parent)
+            .unwrap()
+            .children
+            .retain(|c| *c != pos);
+        parent
+    }
+
+    /// Removes a node and all its descendants from memory, returning its parent.
+    fn remove_subtree(&mut self, pos: Position) -> Position {
+        let mut stack = mem::take(&mut self.nodes.get_mut(&pos).unwrap().children);
+        while let Some(descendant) = stack.pop() {
+            self.close(descendant);
+            let node = self.nodes.remove(&descendant).unwrap();
+            stack.extend(node.children);
+        }
+        self.remove(pos)
+    }
+
+    /// Updates an expanded node after its children changed. Once every child is gone the
+    /// node becomes a leaf again, worth the best forgotten child, or infinite for dead ends.
+    fn refresh(&mut self, pos: Position) {
+        let node = self.nodes.get_mut(&pos).unwrap();
+        if node.children.is_empty() {
+            node.f = node.forgotten_f();
+        }
+        self.reopen(pos);
+    }
+
+    /// Puts a node on the open list if it is a leaf or has forgotten children.
+    fn reopen(&mut self, pos: Position) {
+        self.close(pos);
+
+        let node = self.nodes.get_mut(&pos).unwrap();
+        let value = if node.children.is_empty() {
+            node.f
+        } else if !node.forgotten.is_empty() {
+            node.forgotten_f()
+        } else {
+            return;
+        };
+
+        let key = (value, u32::MAX - node.level, pos.into());
+        node.open = Some(key);
+        self.open.insert(key);
+    }
+
+    fn close(&mut self, pos: Position) {
+        if let Some(key) = self.nodes.get_mut(&pos).and_then(|n| n.open.take()) {
+            self.open.remove(&key);
+        }
+    }
+}
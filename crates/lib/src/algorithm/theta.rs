@@ -0,0 +1,220 @@
+use std::{
+    cmp::max,
+    collections::{BinaryHeap, HashMap, HashSet},
+};
+
+use crate::{
+    channel::ChannelSender,
+    cost::Cost,
+    generator::{ChildrenGenerator, DiagonalMovement, PathInfo, neighbors},
+    heuristics::MazeHeuristic,
+    maze::Maze,
+    position::Position,
+};
+
+use super::{
+    Child, Info, Message, QueueNode, SearchAlgorithm, SearchLimits, StopReason, build_path,
+};
+
+/// Theta*: A* where a node may take the parent of the node it was reached from whenever
+/// the two see each other, giving any-angle paths made of waypoints with
+/// [`Maze::line_cost`] costs. Lines of sight only take the diagonal steps `movement` allows.
+///
+/// Children are generated without a parent, so pruning generators fall back to their
+/// natural neighbours. With `densify` the returned path lists every cell crossed instead of
+/// the waypoints only. Paths are close to, but not always, the shortest any-angle ones, and
+/// get closer with a heuristic that does not exceed the straight-line distance.
+pub struct ThetaStar<'a> {
+    pub maze: &'a Maze,
+    pub movement: DiagonalMovement,
+    pub densify: bool,
+}
+
+impl<'a> ThetaStar<'a> {
+    pub fn new(maze: &'a Maze, movement: DiagonalMovement, densify: bool) -> Self {
+        ThetaStar {
+            maze,
+            movement,
+            densify,
+        }
+    }
+}
+
+/// Lazy Theta*: like [`ThetaStar`], but assumes line of sight when a node is generated
+/// and checks it only once the node is expanded, saving most of the line of sight tests.
+pub struct LazyThetaStar<'a> {
+    pub maze: &'a Maze,
+    pub movement: DiagonalMovement,
+    pub densify: bool,
+}
+
+impl<'a> LazyThetaStar<'a> {
+    pub fn new(maze: &'a Maze, movement: DiagonalMovement, densify: bool) -> Self {
+        LazyThetaStar {
+            maze,
+            movement,
+            densify,
+        }
+    }
+}
+
+impl SearchAlgorithm for ThetaStar<'_> {
+    fn search_with_limits<G: ChildrenGenerator + ?Sized, C: ChannelSender<Message>>(
+        &self,
+        start: Position,
+        goal: Position,
+        heuristic: &dyn MazeHeuristic,
+        generator: &G,
+        channel: C,
+        limits: &SearchLimits,
+    ) -> Info {
+        let info = any_angle(
+            self.maze,
+            self.movement,
+            false,
+            start,
+            goal,
+            heuristic,
+            generator,
+            channel,
+            limits,
+        );
+        densify(info, self.densify)
+    }
+}
+
+impl SearchAlgorithm for LazyThetaStar<'_> {
+    fn search_with_limits<G: ChildrenGenerator + ?Sized, C: ChannelSender<Message>>(
+        &self,
+        start: Position,
+        goal: Position,
+        heuristic: &dyn MazeHeuristic,
+        generator: &G,
+        channel: C,
+        limits: &SearchLimits,
+    ) -> Info {
+        let info = any_angle(
+            self.maze,
+            self.movement,
+            true,
+            start,
+            goal,
+            heuristic,
+            generator,
+            channel,
+            limits,
+        );
+        densify(info, self.densify)
+    }
+}
+
+fn densify(mut info: Info, densify: bool) -> Info {
+    if densify {
+        info.path = info.path.map(|p| p.densify());
+    }
+    info
+}
+
+#[allow(clippy::too_many_arguments)]
+fn any_angle<G: ChildrenGenerator + ?Sized, C: ChannelSender<Message>>(
+    maze: &Maze,
+    movement: DiagonalMovement,
+    lazy: bool,
+    start: Position,
+    goal: Position,
+    heuristic: &dyn MazeHeuristic,
+    generator: &G,
+    channel: C,
+    limits: &SearchLimits,
+) -> Info {
+    let mut depth = HashMap::from([(start, 0)]);
+    let mut parents: HashMap<Position, Position> = HashMap::new();
+    // node each node was generated from, and the depth it was given there
+    let mut via: HashMap<Position, (Position, Cost)> = HashMap::new();
+    let mut queue = BinaryHeap::from([QueueNode::new(start, heuristic.compute_heuristic(start))]);
+    let mut visited = HashSet::new();
+    let mut info = Info::default();
+
+    while let Some(current) = queue.pop() {
+        let current_node = current.node;
+        if visited.contains(&current_node) || current.depth > depth[&current_node] {
+            continue;
+        }
+
+        if let Some(stop) = limits.check(info.nodes, queue.len() + 1) {
+            info.stop = stop;
+            return info;
+        }
+        info.nodes += 1;
+        info.max_length = max(info.max_length, queue.len());
+        visited.insert(current_node);
+
+        let children = generator.generate_children(current_node, None);
+
+        // the parent was assumed to be visible when generating the node: if it is not,
+        // fall back to the best visited neighbour, or to the node it was generated from, as
+        // children may be far from the node, such as the jump points of JPS
+        if lazy
+            && let Some(&parent) = parents.get(&current_node)
+            && !maze.line_of_sight(parent, current_node, movement)
+            && let Some(&generated) = via.get(&current_node)
+        {
+            let (parent, parent_depth) = neighbors(maze, current_node, movement)
+                .into_iter()
+                .filter(|c| visited.contains(&c.node))
+                .map(|c| (c.node, depth[&c.node] + c.weight))
+                .fold(generated, |best, n| if n.1 < best.1 { n } else { best });
+            parents.insert(current_node, parent);
+            depth.insert(current_node, parent_depth);
+        }
+
+        if current_node == goal {
+            let path = build_path(&parents, start, goal);
+            info.path.replace(PathInfo::new(path, depth[&goal]));
+            info.stop = StopReason::Found;
+            return info;
+        }
+
+        let grandparent = parents.get(&current_node).copied();
+        for Child {
+            node: successor,
+            weight,
+        } in children
+        {
+            if visited.contains(&successor) {
+                continue;
+            }
+
+            let (parent, successor_depth) = match grandparent {
+                Some(g) if lazy || maze.line_of_sight(g, successor, movement) => {
+                    (g, depth[&g] + maze.line_cost(g, successor))
+                }
+                _ => (current_node, depth[&current_node] + weight),
+            };
+
+            if successor_depth < *depth.get(&successor).unwrap_or(&Cost::MAX) {
+                parents.insert(successor, parent);
+                depth.insert(successor, successor_depth);
+                if lazy {
+                    via.insert(successor, (current_node, depth[&current_node] + weight));
+                }
+
+                if channel
+                    .send(Message::Enqueued(successor, successor_depth))
+                    .is_err()
+                {
+                    info.stop = StopReason::Disconnected;
+                    return info;
+                }
+
+                queue.push(QueueNode::with_depth(
+                    successor,
+                    heuristic.compute_heuristic(successor),
+                    successor_depth,
+                ));
+            }
+        }
+    }
+
+    info
+}
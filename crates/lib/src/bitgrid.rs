@@ -0,0 +1,105 @@
+const WORD_BITS: u32 = u64::BITS;
+
+/// Packed row-major grid of bits.
+///
+/// Every row starts on a word boundary, so a row can be accessed as a slice of `u64` words
+/// where bit `x % 64` of word `x / 64` holds column `x`. Padding bits past the width are always 0.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitGrid {
+    width: u32,
+    height: u32,
+    stride: usize,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    pub fn new(width: u32, height: u32) -> Self {
+        let stride = width.div_ceil(WORD_BITS) as usize;
+        BitGrid {
+            width,
+            height,
+            stride,
+            words: vec![0; stride * height as usize],
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Number of words used by each row.
+    pub fn stride(&self) -> usize {
+        self.stride
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> bool {
+        let (index, mask) = self.locate(x, y);
+        self.words[index] & mask != 0
+    }
+
+    pub fn set(&mut self, x: u32, y: u32, value: bool) {
+        let (index, mask) = self.locate(x, y);
+        if value {
+            self.words[index] |= mask;
+        } else {
+            self.words[index] &= !mask;
+        }
+    }
+
+    /// Words of row `y`, lowest column first.
+    pub fn row(&self, y: u32) -> &[u64] {
+        let start = y as usize * self.stride;
+        &self.words[start..start + self.stride]
+    }
+
+    /// The grid mirrored along its diagonal, so that columns can be read as rows.
+    pub fn transpose(&self) -> BitGrid {
+        let mut transposed = BitGrid::new(self.height, self.width);
+        for (x, y) in self.ones() {
+            transposed.set(y, x, true);
+        }
+        transposed
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Iterates over the coordinates of the set bits, in row-major order.
+    pub fn ones(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        self.words
+            .iter()
+            .enumerate()
+            .filter(|(_, w)| **w != 0)
+            .flat_map(move |(i, &w)| {
+                let y = (i / self.stride) as u32;
+                let base = ((i % self.stride) as u32) * WORD_BITS;
+                BitIter(w).map(move |b| (base + b, y))
+            })
+    }
+
+    fn locate(&self, x: u32, y: u32) -> (usize, u64) {
+        debug_assert!(x < self.width && y < self.height);
+        let index = y as usize * self.stride + (x / WORD_BITS) as usize;
+        (index, 1 << (x % WORD_BITS))
+    }
+}
+
+struct BitIter(u64);
+
+impl Iterator for BitIter {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        if self.0 == 0 {
+            return None;
+        }
+        let bit = self.0.trailing_zeros();
+        self.0 &= self.0 - 1;
+        Some(bit)
+    }
+}
@@ -0,0 +1,48 @@
+use crate::position::{PosFunctions, Position};
+
+/// Cost of a path in fixed point, [`COST_SCALE`] for one orthogonal step between two cells
+/// of multiplier 1.
+///
+/// Integer costs sum to the same value in any order and compare totally, unlike floats.
+pub type Cost = u64;
+
+pub const COST_SCALE: Cost = 10_000;
+
+/// Cost of a diagonal step between two cells of multiplier 1, `sqrt(2)` in fixed point.
+pub const DIAGONAL_COST: Cost = 14_142;
+
+/// Converts a cost in orthogonal steps, rounding to the nearest [`Cost`].
+pub fn to_cost(steps: f64) -> Cost {
+    scale_cost(COST_SCALE, steps)
+}
+
+/// Converts a cost back into orthogonal steps, for display.
+pub fn to_steps(cost: Cost) -> f64 {
+    cost as f64 / COST_SCALE as f64
+}
+
+/// `cost` multiplied by `factor`, rounded to the nearest [`Cost`]. Negative and NaN
+/// factors give 0.
+pub fn scale_cost(cost: Cost, factor: f64) -> Cost {
+    if factor == 1.0 {
+        cost
+    } else {
+        (cost as f64 * factor).round() as Cost
+    }
+}
+
+/// Length of a straight move by `delta`: exact multiples of [`COST_SCALE`] and
+/// [`DIAGONAL_COST`] along the eight directions, the rounded euclidean length otherwise.
+pub fn segment_length(delta: Position) -> Cost {
+    let (dx, dy) = (
+        delta.x.unsigned_abs() as Cost,
+        delta.y.unsigned_abs() as Cost,
+    );
+    if dx == 0 || dy == 0 {
+        (dx + dy) * COST_SCALE
+    } else if dx == dy {
+        dx * DIAGONAL_COST
+    } else {
+        to_cost(delta.norm())
+    }
+}
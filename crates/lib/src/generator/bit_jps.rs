@@ -0,0 +1,226 @@
+use crate::algorithm::Child;
+use crate::bitgrid::BitGrid;
+use crate::maze::Maze;
+use crate::position::{PosFunctions, Position};
+
+use super::{
+    ChildrenGenerator, DiagonalMovement, Jps4Generator, JpsGenerator, MazePathRef, NodeIndex,
+    PathInfo, has_uniform_neighborhood, segment_cost, walk_path,
+};
+
+const WORD_BITS: i64 = u64::BITS as i64;
+
+/// Jump point search scanning straight runs a word of 64 cells at a time.
+///
+/// Walls, forced neighbours and cells with a non-uniform neighbourhood along a run are found
+/// with bitwise operations on the rows around it, or on the rows of the transposed grid for
+/// vertical runs. Finds the same jump points as [`JpsGenerator`].
+pub struct BitJpsGenerator<'a> {
+    maze: &'a Maze,
+    jps: JpsGenerator<'a>,
+    movement: DiagonalMovement,
+    columns: BitGrid,
+    /// Free cells with a non-uniform neighbourhood, by row and by column.
+    irregular: Option<(BitGrid, BitGrid)>,
+}
+
+impl<'a> BitJpsGenerator<'a> {
+    pub fn new(maze: &'a Maze) -> Self {
+        BitJpsGenerator::with_movement(maze, DiagonalMovement::default())
+    }
+
+    pub fn with_movement(maze: &'a Maze, movement: DiagonalMovement) -> Self {
+        let irregular = (!maze.has_uniform_costs()).then(|| {
+            let mut rows = BitGrid::new(maze.width(), maze.height());
+            for y in 0..maze.height() {
+                for x in 0..maze.width() {
+                    let pos = Position::new(x as i32, y as i32);
+                    if maze.is_free(pos) && !has_uniform_neighborhood(maze, pos) {
+                        rows.set(x, y, true);
+                    }
+                }
+            }
+            let columns = rows.transpose();
+            (rows, columns)
+        });
+
+        BitJpsGenerator {
+            maze,
+            jps: JpsGenerator::with_movement(maze, movement),
+            movement,
+            columns: maze.wall_grid().transpose(),
+            irregular,
+        }
+    }
+
+    fn jump(&self, current: Position, direction: Position) -> Option<Position> {
+        if direction.is_diagonal() {
+            self.jps
+                .jump_with(current, direction, &|from, d| self.jump_straight(from, d))
+        } else {
+            self.jump_straight(current, direction)
+        }
+    }
+
+    fn jump_straight(&self, current: Position, direction: Position) -> Option<Position> {
+        let goal = self.maze.goal();
+        let irregular = self.irregular.as_ref();
+
+        if direction.y == 0 {
+            let lane = Lane {
+                walls: self.maze.wall_grid(),
+                irregular: irregular.map(|(rows, _)| rows),
+                movement: self.movement,
+            };
+            let goal = (goal.y == current.y).then_some(goal.x);
+            lane.scan(current.y, current.x, direction.x > 0, goal)
+                .map(|x| Position::new(x, current.y))
+        } else {
+            let lane = Lane {
+                walls: &self.columns,
+                irregular: irregular.map(|(_, columns)| columns),
+                movement: self.movement,
+            };
+            let goal = (goal.x == current.x).then_some(goal.y);
+            lane.scan(current.x, current.y, direction.y > 0, goal)
+                .map(|y| Position::new(current.x, y))
+        }
+    }
+}
+
+/// Straight scans along the rows of a grid of walls.
+struct Lane<'g> {
+    walls: &'g BitGrid,
+    irregular: Option<&'g BitGrid>,
+    movement: DiagonalMovement,
+}
+
+impl Lane<'_> {
+    /// First jump point past column `from` of row `y`, scanning towards higher columns if
+    /// `forward` and stopping at `goal` too.
+    fn scan(&self, y: i32, from: i32, forward: bool, goal: Option<i32>) -> Option<i32> {
+        let (y, from) = (y as i64, from as i64);
+        let bit = from % WORD_BITS;
+        let mut i = from / WORD_BITS;
+        // only the cells past `from` in its own word
+        let mut mask = if forward {
+            (!0u64).checked_shl(bit as u32 + 1).unwrap_or(0)
+        } else {
+            (1u64 << bit) - 1
+        };
+
+        while self.contains(y, i) {
+            let blocked = !self.free(y, i);
+            let mut jump_points = self.jump_points(y, i, forward);
+            if let Some(goal) = goal.map(i64::from)
+                && goal / WORD_BITS == i
+            {
+                jump_points |= 1 << (goal % WORD_BITS);
+            }
+
+            let stops = (jump_points | blocked) & mask;
+            if stops != 0 {
+                let b = if forward {
+                    stops.trailing_zeros()
+                } else {
+                    63 - stops.leading_zeros()
+                };
+                return (blocked & (1 << b) == 0).then_some((i * WORD_BITS) as i32 + b as i32);
+            }
+
+            mask = !0;
+            i += if forward { 1 } else { -1 };
+        }
+
+        None
+    }
+
+    /// Cells of word `i` of row `y` with a forced neighbour or a non-uniform neighbourhood.
+    fn jump_points(&self, y: i64, i: i64, forward: bool) -> u64 {
+        let mut jump_points = self.irregular.map_or(0, |g| self.word(g, y, i));
+
+        let ahead = |word: &dyn Fn(i64) -> u64| shifted(word, i, forward);
+        let behind = |word: &dyn Fn(i64) -> u64| shifted(word, i, !forward);
+        let free_ahead = ahead(&|j| self.free(y, j));
+
+        for side in [y - 1, y + 1] {
+            let walls = |j| self.word(self.walls, side, j);
+            let free = |j| self.free(side, j);
+            jump_points |= match self.movement {
+                DiagonalMovement::Always => walls(i) & ahead(&free),
+                DiagonalMovement::Never => 0,
+                DiagonalMovement::IfNoObstacles => behind(&walls) & free(i),
+                DiagonalMovement::IfAtMostOneObstacle => walls(i) & ahead(&free) & free_ahead,
+            };
+        }
+
+        jump_points
+    }
+
+    fn contains(&self, y: i64, i: i64) -> bool {
+        (0..self.walls.height() as i64).contains(&y) && (0..self.walls.stride() as i64).contains(&i)
+    }
+
+    /// Word `i` of row `y` of `grid`, 0 outside the grid.
+    fn word(&self, grid: &BitGrid, y: i64, i: i64) -> u64 {
+        if self.contains(y, i) {
+            grid.row(y as u32)[i as usize]
+        } else {
+            0
+        }
+    }
+
+    /// Free cells of word `i` of row `y`, 0 outside the grid.
+    fn free(&self, y: i64, i: i64) -> u64 {
+        if !self.contains(y, i) {
+            return 0;
+        }
+
+        let width = self.walls.width() as i64;
+        let valid = if (i + 1) * WORD_BITS > width {
+            (1u64 << (width % WORD_BITS)) - 1
+        } else {
+            !0
+        };
+        !self.word(self.walls, y, i) & valid
+    }
+}
+
+/// Bits of word `i` of a row holding, for each cell, the bit of its neighbour towards higher
+/// columns if `higher`, or towards lower ones otherwise.
+fn shifted(word: &dyn Fn(i64) -> u64, i: i64, higher: bool) -> u64 {
+    if higher {
+        (word(i) >> 1) | (word(i + 1) << 63)
+    } else {
+        (word(i) << 1) | (word(i - 1) >> 63)
+    }
+}
+
+impl ChildrenGenerator for BitJpsGenerator<'_> {
+    fn generate_children(&self, current: Position, parent: Option<Position>) -> Vec<Child> {
+        if self.movement == DiagonalMovement::Never {
+            return Jps4Generator::new(self.maze).generate_children(current, parent);
+        }
+
+        let Some(parent) = parent else {
+            return self.jps.natural_neighbors(current);
+        };
+
+        self.jps
+            .pruned_neighbors(current, parent)
+            .into_iter()
+            .filter_map(|n| {
+                self.jump(current, n.node - current)
+                    .map(|j| Child::new(j, segment_cost(self.maze, current, j)))
+            })
+            .collect()
+    }
+
+    fn reconstruct_path(&self, path: MazePathRef) -> PathInfo {
+        walk_path(self.maze, path)
+    }
+
+    fn node_index(&self) -> Option<&dyn NodeIndex> {
+        Some(self.maze)
+    }
+}
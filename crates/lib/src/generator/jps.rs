@@ -0,0 +1,183 @@
+use crate::algorithm::Child;
+use crate::maze::Maze;
+use crate::position::{PosFunctions, Position};
+
+use super::{
+    ChildrenGenerator, DiagonalMovement, Jps4Generator, MazePathRef, NodeIndex, PathInfo,
+    has_uniform_neighborhood, neighbors, segment_cost, walk_path,
+};
+
+/// Jump point search on 8-connected mazes, following a [`DiagonalMovement`] policy.
+/// With [`DiagonalMovement::Never`] it behaves as [`Jps4Generator`].
+pub struct JpsGenerator<'a> {
+    maze: &'a Maze,
+    movement: DiagonalMovement,
+}
+
+impl<'a> JpsGenerator<'a> {
+    pub fn new(maze: &'a Maze) -> Self {
+        JpsGenerator::with_movement(maze, DiagonalMovement::default())
+    }
+
+    pub fn with_movement(maze: &'a Maze, movement: DiagonalMovement) -> Self {
+        JpsGenerator { maze, movement }
+    }
+
+    pub(super) fn natural_neighbors(&self, current: Position) -> Vec<Child> {
+        neighbors(self.maze, current, self.movement)
+    }
+
+    pub(super) fn has_uniform_neighborhood(&self, pos: Position) -> bool {
+        has_uniform_neighborhood(self.maze, pos)
+    }
+
+    pub(super) fn allows(&self, from: Position, to: Position) -> bool {
+        self.maze.is_free(to) && self.movement.allows(self.maze, from, to - from)
+    }
+
+    /// Natural neighbours of `current` worth jumping towards when coming from `parent`.
+    pub(super) fn pruned_neighbors(&self, current: Position, parent: Position) -> Vec<Child> {
+        let mut natural_neighbors = self.natural_neighbors(current);
+        if self.has_uniform_neighborhood(current) {
+            self.prune_neighbors(current, parent, &mut natural_neighbors);
+        }
+        natural_neighbors
+    }
+
+    fn prune_neighbors(&self, current: Position, parent: Position, vec: &mut Vec<Child>) {
+        let mv = (current - parent).signum();
+        if mv.is_diagonal() {
+            self.prune_diagonal(vec, current, mv);
+        } else {
+            self.prune_straight(vec, current, mv);
+        }
+    }
+
+    fn do_jump(&self, current: Position, vec: Vec<Child>) -> Vec<Child> {
+        vec.into_iter()
+            .filter_map(|p| {
+                self.jump(current, p.node - current)
+                    .map(|j| Child::new(j, segment_cost(self.maze, current, j)))
+            })
+            .collect()
+    }
+
+    fn prune_diagonal(&self, vec: &mut Vec<Child>, current: Position, mv: Position) {
+        let mut pruned_list = vec![current + mv];
+        pruned_list.extend(mv.components().iter().map(|&p| current + p));
+        pruned_list.extend_from_slice(&self.compute_forced_diagonal(current - mv, mv));
+        vec.retain(|p| pruned_list.contains(&p.node));
+    }
+
+    fn prune_straight(&self, vec: &mut Vec<Child>, current: Position, mv: Position) {
+        let mut pruned_list = vec![current + mv];
+        pruned_list.extend_from_slice(&self.compute_forced_straight(current, mv));
+        vec.retain(|p| pruned_list.contains(&p.node))
+    }
+
+    /// Neighbours of `current` that can only be reached optimally through it, while moving
+    /// straight along `mv`. Without corner cutting a wall behind the side cell opens it up,
+    /// otherwise a wall beside `current` opens up the diagonal past it.
+    pub(super) fn compute_forced_straight(&self, current: Position, mv: Position) -> Vec<Position> {
+        if self.movement == DiagonalMovement::IfNoObstacles {
+            return mv
+                .orthogonal()
+                .into_iter()
+                .filter(|&o| self.maze.is_wall(current - mv + o))
+                .flat_map(|o| [current + o, current + mv + o])
+                .collect();
+        }
+        self.compute_forced(&mv.orthogonal(), current, Some(mv))
+    }
+
+    /// Like [`Self::compute_forced_straight`], for the diagonal move from `current` along
+    /// `mv`. Without corner cutting both sides of a diagonal move are free, so nothing is forced.
+    pub(super) fn compute_forced_diagonal(&self, current: Position, mv: Position) -> Vec<Position> {
+        if self.movement == DiagonalMovement::IfNoObstacles {
+            return Vec::new();
+        }
+        self.compute_forced(&mv.components(), current, None)
+    }
+
+    fn compute_forced(
+        &self,
+        positions: &[Position],
+        current: Position,
+        add: Option<Position>,
+    ) -> Vec<Position> {
+        positions
+            .iter()
+            .filter_map(|&dir| {
+                let n = current + dir;
+                self.maze.is_wall(n).then(|| n + add.unwrap_or(dir))
+            })
+            .collect()
+    }
+
+    /// Scans from `current` along `direction` until a jump point is found, one cell per
+    /// iteration. Diagonal scans run a straight scan along both components from every cell.
+    fn jump(&self, current: Position, direction: Position) -> Option<Position> {
+        self.jump_with(current, direction, &|from, d| self.jump(from, d))
+    }
+
+    /// Like [`Self::jump`], running the straight scans of diagonal moves with `straight`.
+    pub(super) fn jump_with(
+        &self,
+        mut current: Position,
+        direction: Position,
+        straight: &dyn Fn(Position, Position) -> Option<Position>,
+    ) -> Option<Position> {
+        let goal = self.maze.goal();
+        loop {
+            let next = current + direction;
+            if !self.allows(current, next) {
+                return None;
+            }
+            if next == goal || !self.has_uniform_neighborhood(next) {
+                return Some(next);
+            }
+
+            let forced = if direction.is_diagonal() {
+                self.compute_forced_diagonal(current, direction)
+            } else {
+                self.compute_forced_straight(next, direction)
+            };
+
+            if forced.iter().any(|&f| self.allows(next, f)) {
+                return Some(next);
+            }
+
+            if direction.is_diagonal()
+                && direction
+                    .components()
+                    .iter()
+                    .any(|&d| straight(next, d).is_some())
+            {
+                return Some(next);
+            }
+
+            current = next;
+        }
+    }
+}
+
+impl ChildrenGenerator for JpsGenerator<'_> {
+    fn generate_children(&self, current: Position, parent: Option<Position>) -> Vec<Child> {
+        if self.movement == DiagonalMovement::Never {
+            return Jps4Generator::new(self.maze).generate_children(current, parent);
+        }
+
+        match parent {
+            None => self.natural_neighbors(current),
+            Some(parent) => self.do_jump(current, self.pruned_neighbors(current, parent)),
+        }
+    }
+
+    fn reconstruct_path(&self, path: MazePathRef) -> PathInfo {
+        walk_path(self.maze, path)
+    }
+
+    fn node_index(&self) -> Option<&dyn NodeIndex> {
+        Some(self.maze)
+    }
+}
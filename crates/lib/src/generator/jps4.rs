@@ -0,0 +1,126 @@
+use crate::algorithm::Child;
+use crate::maze::Maze;
+use crate::position::{PosFunctions, Position};
+
+use super::{
+    ChildrenGenerator, DiagonalMovement, MazePathRef, NodeIndex, PathInfo,
+    has_uniform_neighborhood, neighbors, segment_cost, walk_path,
+};
+
+/// Jump point search on 4-connected mazes.
+///
+/// Canonical paths move vertically first: vertical jumps scan horizontally from every cell
+/// they cross, and stop where one of those scans finds a jump point. Horizontal jumps stop
+/// next to the corner of an obstacle, where a vertical move becomes possible.
+pub struct Jps4Generator<'a> {
+    maze: &'a Maze,
+}
+
+impl<'a> Jps4Generator<'a> {
+    pub fn new(maze: &'a Maze) -> Self {
+        Jps4Generator { maze }
+    }
+
+    /// Pruning compares paths through the diagonal neighbours too, so they must share the
+    /// cost of `pos` as well.
+    fn has_uniform_neighborhood(&self, pos: Position) -> bool {
+        has_uniform_neighborhood(self.maze, pos)
+    }
+
+    /// Vertical moves that open up at `pos` while moving horizontally along `mv`.
+    pub(super) fn forced_neighbors(&self, pos: Position, mv: Position) -> Vec<Position> {
+        [Position::NEG_Y, Position::Y]
+            .into_iter()
+            .filter(|&o| self.maze.is_free(pos + o) && self.maze.is_wall(pos - mv + o))
+            .map(|o| pos + o)
+            .collect()
+    }
+
+    /// Natural neighbours of `current` worth jumping towards when coming from `parent`.
+    pub(super) fn pruned_neighbors(&self, current: Position, parent: Position) -> Vec<Child> {
+        let mut natural_neighbors = neighbors(self.maze, current, DiagonalMovement::Never);
+        if self.has_uniform_neighborhood(current) {
+            self.prune_neighbors(current, parent, &mut natural_neighbors);
+        }
+        natural_neighbors
+    }
+
+    fn prune_neighbors(&self, current: Position, parent: Position, vec: &mut Vec<Child>) {
+        let mv = (current - parent).signum();
+        let mut pruned_list = vec![current + mv];
+        if mv.x == 0 {
+            pruned_list.extend(mv.orthogonal().iter().map(|&o| current + o));
+        } else {
+            pruned_list.extend(self.forced_neighbors(current, mv));
+        }
+        vec.retain(|p| pruned_list.contains(&p.node));
+    }
+
+    fn jump(&self, current: Position, direction: Position) -> Option<Position> {
+        if direction.x == 0 {
+            self.jump_vertical(current, direction)
+        } else {
+            self.jump_horizontal(current, direction)
+        }
+    }
+
+    fn jump_horizontal(&self, mut current: Position, direction: Position) -> Option<Position> {
+        let goal = self.maze.goal();
+        loop {
+            current += direction;
+            if !self.maze.is_free(current) {
+                return None;
+            }
+            if current == goal
+                || !self.has_uniform_neighborhood(current)
+                || !self.forced_neighbors(current, direction).is_empty()
+            {
+                return Some(current);
+            }
+        }
+    }
+
+    fn jump_vertical(&self, mut current: Position, direction: Position) -> Option<Position> {
+        let goal = self.maze.goal();
+        loop {
+            current += direction;
+            if !self.maze.is_free(current) {
+                return None;
+            }
+            if current == goal || !self.has_uniform_neighborhood(current) {
+                return Some(current);
+            }
+            if direction
+                .orthogonal()
+                .iter()
+                .any(|&h| self.jump_horizontal(current, h).is_some())
+            {
+                return Some(current);
+            }
+        }
+    }
+}
+
+impl ChildrenGenerator for Jps4Generator<'_> {
+    fn generate_children(&self, current: Position, parent: Option<Position>) -> Vec<Child> {
+        let Some(parent) = parent else {
+            return neighbors(self.maze, current, DiagonalMovement::Never);
+        };
+
+        self.pruned_neighbors(current, parent)
+            .into_iter()
+            .filter_map(|n| {
+                self.jump(current, n.node - current)
+                    .map(|j| Child::new(j, segment_cost(self.maze, current, j)))
+            })
+            .collect()
+    }
+
+    fn reconstruct_path(&self, path: MazePathRef) -> PathInfo {
+        walk_path(self.maze, path)
+    }
+
+    fn node_index(&self) -> Option<&dyn NodeIndex> {
+        Some(self.maze)
+    }
+}
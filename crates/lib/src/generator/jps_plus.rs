@@ -0,0 +1,288 @@
+use crate::algorithm::Child;
+use crate::maze::Maze;
+use crate::position::{PosFunctions, Position};
+
+use super::{
+    ChildrenGenerator, DiagonalMovement, EIGHT_WAY, Jps4Generator, JpsGenerator, MazePathRef,
+    NodeIndex, PathInfo, segment_cost, walk_path,
+};
+
+/// Jump distances of every cell of a maze towards each of the 8 directions, as found by
+/// [`JpsGenerator`] ignoring the goal. With [`DiagonalMovement::Never`] the straight
+/// distances follow [`Jps4Generator`] instead, and the diagonal ones are all 0.
+///
+/// A positive distance is the number of steps to the next jump point, otherwise its opposite
+/// is the number of steps that can be taken before hitting a wall. Runs longer than
+/// `i16::MAX` cells are split by extra jump points.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JumpTable {
+    width: u32,
+    height: u32,
+    movement: DiagonalMovement,
+    fingerprint: u64,
+    distances: Vec<i16>,
+}
+
+impl JumpTable {
+    pub fn new(maze: &Maze, movement: DiagonalMovement) -> Self {
+        let (width, height) = (maze.width(), maze.height());
+        let mut table = JumpTable {
+            width,
+            height,
+            movement,
+            fingerprint: maze.fingerprint(),
+            distances: vec![0; width as usize * height as usize * EIGHT_WAY.len()],
+        };
+
+        let jps = JpsGenerator::with_movement(maze, movement);
+        let jps4 = Jps4Generator::new(maze);
+        let order = |forward: bool, len: u32| -> Vec<u32> {
+            if forward {
+                (0..len).rev().collect()
+            } else {
+                (0..len).collect()
+            }
+        };
+
+        // scans come after the ones they look up: horizontal, vertical, then diagonal
+        let mut directions = EIGHT_WAY;
+        directions.sort_by_key(|d| (d.is_diagonal(), d.x == 0));
+        for direction in directions {
+            let xs = order(direction.x > 0, width);
+            for y in order(direction.y > 0, height) {
+                for &x in &xs {
+                    let pos = Position::new(x as i32, y as i32);
+                    if maze.is_free(pos) {
+                        let index = table.index(pos) + slot(direction);
+                        table.distances[index] = table.scan(&jps, &jps4, pos, direction);
+                    }
+                }
+            }
+        }
+
+        table
+    }
+
+    /// Rebuilds a table from the values returned by the accessors of a saved one.
+    pub fn from_parts(
+        width: u32,
+        height: u32,
+        movement: DiagonalMovement,
+        fingerprint: u64,
+        distances: Vec<i16>,
+    ) -> Result<Self, String> {
+        let expected = width as usize * height as usize * EIGHT_WAY.len();
+        if distances.len() != expected {
+            return Err(format!(
+                "Invalid jump table: {} distances, expected {}",
+                distances.len(),
+                expected
+            ));
+        }
+
+        Ok(JumpTable {
+            width,
+            height,
+            movement,
+            fingerprint,
+            distances,
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn movement(&self) -> DiagonalMovement {
+        self.movement
+    }
+
+    /// Hash of the walls and costs of the maze the table was built for.
+    pub fn fingerprint(&self) -> u64 {
+        self.fingerprint
+    }
+
+    /// All the distances, 8 per cell in row-major order.
+    pub fn distances(&self) -> &[i16] {
+        &self.distances
+    }
+
+    /// Whether the table was built for a maze with the same walls and costs as `maze`.
+    pub fn matches(&self, maze: &Maze) -> bool {
+        self.width == maze.width()
+            && self.height == maze.height()
+            && self.fingerprint == maze.fingerprint()
+    }
+
+    /// Jump distance from `pos` along one of the 8 unit `direction`s.
+    pub fn distance(&self, pos: Position, direction: Position) -> i16 {
+        self.distances[self.index(pos) + slot(direction)]
+    }
+
+    fn index(&self, pos: Position) -> usize {
+        (pos.y as usize * self.width as usize + pos.x as usize) * EIGHT_WAY.len()
+    }
+
+    /// Directions scanned from every cell crossed by a jump along `direction`: the
+    /// components of a diagonal, or the horizontal ones for a vertical jump on 4-connected
+    /// mazes.
+    fn sides(&self, direction: Position) -> Option<[Position; 2]> {
+        if direction.is_diagonal() {
+            Some(direction.components())
+        } else if direction.x == 0 && self.movement == DiagonalMovement::Never {
+            Some(direction.orthogonal())
+        } else {
+            None
+        }
+    }
+
+    /// Distance from `current` along `direction`, given the distances of the next cell.
+    fn scan(
+        &self,
+        jps: &JpsGenerator,
+        jps4: &Jps4Generator,
+        current: Position,
+        direction: Position,
+    ) -> i16 {
+        let next = current + direction;
+        if !jps.allows(current, next) {
+            return 0;
+        }
+
+        let forced = if direction.is_diagonal() {
+            jps.compute_forced_diagonal(current, direction)
+        } else if self.movement != DiagonalMovement::Never {
+            jps.compute_forced_straight(next, direction)
+        } else if direction.y == 0 {
+            jps4.forced_neighbors(next, direction)
+        } else {
+            Vec::new()
+        };
+        let is_jump_point = !jps.has_uniform_neighborhood(next)
+            || forced.iter().any(|&f| jps.allows(next, f))
+            || self
+                .sides(direction)
+                .is_some_and(|sides| sides.iter().any(|&d| self.distance(next, d) > 0));
+        if is_jump_point {
+            return 1;
+        }
+
+        match self.distance(next, direction) {
+            d if d > 0 => d.checked_add(1).unwrap_or(1),
+            d => d.checked_sub(1).unwrap_or(1),
+        }
+    }
+}
+
+/// Index of one of the 8 unit `direction`s among the distances of a cell.
+fn slot(direction: Position) -> usize {
+    EIGHT_WAY
+        .iter()
+        .position(|&d| d == direction)
+        .expect("not a unit direction")
+}
+
+/// JPS+: jump point search reading jump points from a precomputed [`JumpTable`] instead of
+/// scanning the maze, giving the same paths as [`JpsGenerator`] with the table's
+/// [`DiagonalMovement`].
+pub struct JpsPlusGenerator<'a> {
+    maze: &'a Maze,
+    jps: JpsGenerator<'a>,
+    jps4: Jps4Generator<'a>,
+    table: &'a JumpTable,
+}
+
+impl<'a> JpsPlusGenerator<'a> {
+    /// Fails if `table` was built for another maze or another `movement`.
+    pub fn new(
+        maze: &'a Maze,
+        table: &'a JumpTable,
+        movement: DiagonalMovement,
+    ) -> Result<Self, String> {
+        if !table.matches(maze) {
+            return Err("Jump table built for another maze".to_string());
+        }
+        if table.movement != movement {
+            return Err(format!(
+                "Jump table built for {:?} diagonal movement, not {:?}",
+                table.movement, movement
+            ));
+        }
+
+        Ok(JpsPlusGenerator {
+            maze,
+            jps: JpsGenerator::with_movement(maze, movement),
+            jps4: Jps4Generator::new(maze),
+            table,
+        })
+    }
+
+    fn jump(&self, current: Position, direction: Position) -> Option<Position> {
+        let distance = self.table.distance(current, direction);
+        let reach = distance.unsigned_abs() as i32;
+
+        self.goal_target(current, direction, reach)
+            .or_else(|| (distance > 0).then(|| current + direction * distance as i32))
+    }
+
+    /// The jump point the goal adds within `reach` steps of `current` along `direction`:
+    /// the goal itself, or the cell from which a scan along one of the sides reaches it.
+    fn goal_target(&self, current: Position, direction: Position, reach: i32) -> Option<Position> {
+        let goal = self.maze.goal();
+        let to_goal = goal - current;
+        let sides = self.table.sides(direction);
+
+        // the jump crosses the column and the row of the goal, nearest first
+        let mut crossings = [to_goal.x * direction.x, to_goal.y * direction.y];
+        crossings.sort();
+        crossings
+            .into_iter()
+            .filter(|k| (1..=reach).contains(k))
+            .find_map(|k| {
+                let cell = current + direction * k;
+                let rest = goal - cell;
+                if rest == Position::ZERO {
+                    return Some(cell);
+                }
+
+                let towards = rest.signum();
+                let steps = rest.abs().max_element();
+                let straight = sides.is_some_and(|sides| sides.contains(&towards))
+                    && steps <= self.table.distance(cell, towards).unsigned_abs() as i32;
+                straight.then_some(cell)
+            })
+    }
+}
+
+impl ChildrenGenerator for JpsPlusGenerator<'_> {
+    fn generate_children(&self, current: Position, parent: Option<Position>) -> Vec<Child> {
+        let Some(parent) = parent else {
+            return self.jps.natural_neighbors(current);
+        };
+
+        let pruned = if self.table.movement == DiagonalMovement::Never {
+            self.jps4.pruned_neighbors(current, parent)
+        } else {
+            self.jps.pruned_neighbors(current, parent)
+        };
+        pruned
+            .into_iter()
+            .filter_map(|n| {
+                self.jump(current, n.node - current)
+                    .map(|j| Child::new(j, segment_cost(self.maze, current, j)))
+            })
+            .collect()
+    }
+
+    fn reconstruct_path(&self, path: MazePathRef) -> PathInfo {
+        walk_path(self.maze, path)
+    }
+
+    fn node_index(&self) -> Option<&dyn NodeIndex> {
+        Some(self.maze)
+    }
+}
@@ -0,0 +1,192 @@
+mod bit_jps;
+mod jps;
+mod jps4;
+mod jps_plus;
+mod neighbors;
+
+use itertools::Itertools;
+
+use crate::algorithm::Child;
+use crate::cost::{Cost, segment_length};
+use crate::maze::Maze;
+use crate::position::{PosFunctions, Position, line};
+
+pub use bit_jps::BitJpsGenerator;
+pub use jps::JpsGenerator;
+pub use jps_plus::{JpsPlusGenerator, JumpTable};
+pub use jps4::Jps4Generator;
+pub use neighbors::{EightNeighborGenerator, FourNeighborGenerator};
+
+pub type MazePath = Vec<Position>;
+pub type MazePathRef<'a> = &'a [Position];
+
+#[derive(Clone, Debug, Default)]
+pub struct PathInfo {
+    pub path: MazePath,
+    pub cost: Cost,
+}
+
+impl PathInfo {
+    pub fn new(path: MazePath, cost: Cost) -> Self {
+        PathInfo { path, cost }
+    }
+
+    pub fn path_len(&self) -> usize {
+        self.path.len()
+    }
+
+    /// Expands a path of waypoints, such as the ones of any-angle searches, into the
+    /// cells crossed by each segment. The cost is unchanged.
+    pub fn densify(&self) -> PathInfo {
+        let mut path = vec![];
+        for (&from, &to) in self.path.iter().tuple_windows() {
+            path.pop();
+            path.extend(line(from, to));
+        }
+        if path.is_empty() {
+            path.clone_from(&self.path);
+        }
+        PathInfo::new(path, self.cost)
+    }
+}
+
+pub trait ChildrenGenerator {
+    fn generate_children(&self, current: Position, parent: Option<Position>) -> Vec<Child>;
+    fn reconstruct_path(&self, path: MazePathRef) -> PathInfo;
+    /// Numbering of the nodes, which searches index their state by. Without one, searches
+    /// number the nodes as they reach them, in a hash map.
+    fn node_index(&self) -> Option<&dyn NodeIndex> {
+        None
+    }
+}
+
+/// Dense numbering of the nodes of a graph.
+pub trait NodeIndex {
+    /// Number of nodes, above every index.
+    fn node_count(&self) -> usize;
+    /// Index of `node`, or `None` if it is not a node of the graph.
+    fn index_of(&self, node: Position) -> Option<usize>;
+    /// Node of `index`, below [`NodeIndex::node_count`].
+    fn node_at(&self, index: usize) -> Position;
+}
+
+/// Cells in row-major order.
+impl NodeIndex for Maze {
+    fn node_count(&self) -> usize {
+        self.width() as usize * self.height() as usize
+    }
+
+    fn index_of(&self, node: Position) -> Option<usize> {
+        self.valid(node)
+            .then(|| node.y as usize * self.width() as usize + node.x as usize)
+    }
+
+    fn node_at(&self, index: usize) -> Position {
+        let width = self.width() as usize;
+        Position::new((index % width) as i32, (index / width) as i32)
+    }
+}
+
+const EIGHT_WAY: [Position; 8] = [
+    Position::NEG_Y,
+    Position::Y,
+    Position::NEG_X,
+    Position::X,
+    Position::new(-1, -1),
+    Position::new(1, -1),
+    Position::new(-1, 1),
+    Position::new(1, 1),
+];
+
+/// When a diagonal step is allowed, depending on the two cells it passes between.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DiagonalMovement {
+    /// Whenever the target cell is free, even between two walls touching at a corner.
+    Always,
+    /// Never: the maze is 4-connected.
+    Never,
+    /// Only if both orthogonal cells are free, so that paths never cut corners.
+    IfNoObstacles,
+    /// Unless both orthogonal cells are walls.
+    #[default]
+    IfAtMostOneObstacle,
+}
+
+impl DiagonalMovement {
+    /// Whether the move from `from` along `dir` is allowed, assuming the target is free.
+    pub fn allows(self, maze: &Maze, from: Position, dir: Position) -> bool {
+        if !dir.is_diagonal() {
+            return true;
+        }
+
+        let free = || dir.components().map(|c| maze.is_free(from + c));
+        match self {
+            DiagonalMovement::Always => true,
+            DiagonalMovement::Never => false,
+            DiagonalMovement::IfNoObstacles => free().into_iter().all(|f| f),
+            DiagonalMovement::IfAtMostOneObstacle => free().into_iter().any(|f| f),
+        }
+    }
+}
+
+/// Free neighbours of `pos` reachable under `movement`, weighted by their step cost.
+pub(crate) fn neighbors(maze: &Maze, pos: Position, movement: DiagonalMovement) -> Vec<Child> {
+    EIGHT_WAY
+        .iter()
+        .filter(|&&d| maze.is_free(pos + d) && movement.allows(maze, pos, d))
+        .map(|&d| Child::new(pos + d, maze.step_cost(pos, pos + d)))
+        .collect()
+}
+
+/// Whether every free cell around `pos` has the same cost as `pos`. Pruning and jumping
+/// are only valid inside such regions, so any other cell is treated as a jump point.
+fn has_uniform_neighborhood(maze: &Maze, pos: Position) -> bool {
+    if maze.has_uniform_costs() {
+        return true;
+    }
+
+    let cost = maze.cost(pos);
+    EIGHT_WAY
+        .iter()
+        .map(|&d| pos + d)
+        .filter(|&n| maze.is_free(n))
+        .all(|n| maze.cost(n) == cost)
+}
+
+/// Cost of a straight or diagonal segment, one step at a time.
+fn segment_cost(maze: &Maze, from: Position, to: Position) -> Cost {
+    if maze.has_uniform_costs() {
+        return segment_length(to - from);
+    }
+
+    let dir = (to - from).signum();
+    let mut cost = 0;
+    let mut cur = from;
+    while cur != to {
+        cost += maze.step_cost(cur, cur + dir);
+        cur += dir;
+    }
+    cost
+}
+
+/// Expands a path made of straight or diagonal segments into single steps.
+fn walk_path(maze: &Maze, path: MazePathRef) -> PathInfo {
+    if path.is_empty() {
+        return PathInfo::default();
+    }
+
+    let mut result = vec![*path.first().unwrap()];
+    let mut cost = 0;
+
+    for (cur, next) in path.iter().tuple_windows() {
+        let dir = (*next - *cur).signum();
+        let mut cur = *cur;
+        while cur != *next {
+            cost += maze.step_cost(cur, cur + dir);
+            cur += dir;
+            result.push(cur);
+        }
+    }
+
+    PathInfo::new(result, cost)
+}
@@ -0,0 +1,63 @@
+use crate::algorithm::Child;
+use crate::maze::Maze;
+use crate::position::Position;
+
+use super::{
+    ChildrenGenerator, DiagonalMovement, MazePathRef, NodeIndex, PathInfo, neighbors, walk_path,
+};
+
+/// Expands every free orthogonal neighbour, for 4-connected mazes.
+pub struct FourNeighborGenerator<'a> {
+    maze: &'a Maze,
+}
+
+impl<'a> FourNeighborGenerator<'a> {
+    pub fn new(maze: &'a Maze) -> Self {
+        FourNeighborGenerator { maze }
+    }
+}
+
+impl ChildrenGenerator for FourNeighborGenerator<'_> {
+    fn generate_children(&self, current: Position, _parent: Option<Position>) -> Vec<Child> {
+        neighbors(self.maze, current, DiagonalMovement::Never)
+    }
+
+    fn reconstruct_path(&self, path: MazePathRef) -> PathInfo {
+        walk_path(self.maze, path)
+    }
+
+    fn node_index(&self) -> Option<&dyn NodeIndex> {
+        Some(self.maze)
+    }
+}
+
+/// Expands every neighbour allowed by the [`DiagonalMovement`] policy, without any pruning.
+/// Explores the same graph as [`JpsGenerator`](super::JpsGenerator), one cell at a time.
+pub struct EightNeighborGenerator<'a> {
+    maze: &'a Maze,
+    movement: DiagonalMovement,
+}
+
+impl<'a> EightNeighborGenerator<'a> {
+    pub fn new(maze: &'a Maze) -> Self {
+        EightNeighborGenerator::with_movement(maze, DiagonalMovement::default())
+    }
+
+    pub fn with_movement(maze: &'a Maze, movement: DiagonalMovement) -> Self {
+        EightNeighborGenerator { maze, movement }
+    }
+}
+
+impl ChildrenGenerator for EightNeighborGenerator<'_> {
+    fn generate_children(&self, current: Position, _parent: Option<Position>) -> Vec<Child> {
+        neighbors(self.maze, current, self.movement)
+    }
+
+    fn reconstruct_path(&self, path: MazePathRef) -> PathInfo {
+        walk_path(self.maze, path)
+    }
+
+    fn node_index(&self) -> Option<&dyn NodeIndex> {
+        Some(self.maze)
+    }
+}
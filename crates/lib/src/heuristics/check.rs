@@ -0,0 +1,196 @@
+use std::{cmp::Reverse, collections::BinaryHeap, fmt};
+
+use crate::{
+    cost::{Cost, to_steps},
+    generator::ChildrenGenerator,
+    maze::Maze,
+    position::Position,
+};
+
+use super::MazeHeuristic;
+
+/// A cell whose heuristic is higher than the cost of its cheapest path to the goal.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Overestimate {
+    pub cell: Position,
+    pub heuristic: Cost,
+    pub cost: Cost,
+}
+
+/// A move along which the heuristic drops by more than the cost of the move.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Inconsistency {
+    pub from: Position,
+    pub to: Position,
+    pub weight: Cost,
+    /// Heuristic of `from` minus the one of `to`.
+    pub drop: Cost,
+}
+
+/// Outcome of [`check_heuristic`].
+#[derive(Debug, Default)]
+pub struct HeuristicReport {
+    /// Free cells checked.
+    pub cells: usize,
+    /// Cells from which the goal can be reached.
+    pub reachable: usize,
+    /// Moves checked for consistency.
+    pub moves: usize,
+    /// Cells where the heuristic is not admissible, in row-major order.
+    pub overestimates: Vec<Overestimate>,
+    /// Moves where the heuristic is not consistent, in row-major order of their origin.
+    pub inconsistencies: Vec<Inconsistency>,
+}
+
+impl HeuristicReport {
+    pub fn is_admissible(&self) -> bool {
+        self.overestimates.is_empty()
+    }
+
+    pub fn is_consistent(&self) -> bool {
+        self.inconsistencies.is_empty()
+    }
+
+    /// The overestimate exceeding the true cost the most.
+    pub fn worst_overestimate(&self) -> Option<&Overestimate> {
+        self.overestimates
+            .iter()
+            .max_by_key(|o| o.heuristic - o.cost)
+    }
+
+    /// The inconsistency whose drop exceeds the cost of its move the most.
+    pub fn worst_inconsistency(&self) -> Option<&Inconsistency> {
+        self.inconsistencies
+            .iter()
+            .max_by_key(|i| i.drop - i.weight)
+    }
+}
+
+impl fmt::Display for HeuristicReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} cells, {} reaching the goal, {} moves",
+            self.cells, self.reachable, self.moves
+        )?;
+
+        match self.worst_overestimate() {
+            None => writeln!(f, "Admissible")?,
+            Some(worst) => writeln!(
+                f,
+                "Not admissible: {} overestimates, worst at {}: {} for a cost of {}",
+                self.overestimates.len(),
+                worst.cell,
+                to_steps(worst.heuristic),
+                to_steps(worst.cost)
+            )?,
+        }
+
+        match self.worst_inconsistency() {
+            None => write!(f, "Consistent"),
+            Some(worst) => write!(
+                f,
+                "Not consistent: {} moves, worst from {} to {}: drop of {} for a cost of {}",
+                self.inconsistencies.len(),
+                worst.from,
+                worst.to,
+                to_steps(worst.drop),
+                to_steps(worst.weight)
+            ),
+        }
+    }
+}
+
+/// Checks `heuristic` against the exact cost to the goal of `maze` from every free cell,
+/// found with a Dijkstra search from the goal over the moves of `generator` reversed.
+///
+/// The moves of a cell are the children generated without a parent, that is the ones
+/// searched when starting from it.
+pub fn check_heuristic<G: ChildrenGenerator + ?Sized>(
+    maze: &Maze,
+    generator: &G,
+    heuristic: &dyn MazeHeuristic,
+) -> HeuristicReport {
+    let width = maze.width() as usize;
+    let cells = width * maze.height() as usize;
+    let index = |pos: Position| pos.y as usize * width + pos.x as usize;
+    let position = |i: usize| Position::new((i % width) as i32, (i / width) as i32);
+    let free: Vec<_> = (0..cells).filter(|&i| maze.is_free(position(i))).collect();
+
+    let mut report = HeuristicReport {
+        cells: free.len(),
+        ..HeuristicReport::default()
+    };
+
+    // the moves are generated twice, first checking consistency and counting the moves
+    // reaching each cell, then storing them reversed
+    let mut offsets = vec![0; cells + 1];
+    for &i in &free {
+        let from = position(i);
+        let from_heuristic = heuristic.compute_heuristic(from);
+        for child in generator.generate_children(from, None) {
+            offsets[index(child.node) + 1] += 1;
+            report.moves += 1;
+
+            let to_heuristic = heuristic.compute_heuristic(child.node);
+            if from_heuristic > child.weight + to_heuristic {
+                report.inconsistencies.push(Inconsistency {
+                    from,
+                    to: child.node,
+                    weight: child.weight,
+                    drop: from_heuristic - to_heuristic,
+                });
+            }
+        }
+    }
+    for i in 0..cells {
+        offsets[i + 1] += offsets[i];
+    }
+
+    let mut parents = vec![(0, 0); report.moves];
+    let mut filled = offsets.clone();
+    for &i in &free {
+        for child in generator.generate_children(position(i), None) {
+            let j = index(child.node);
+            parents[filled[j]] = (i, child.weight);
+            filled[j] += 1;
+        }
+    }
+
+    let mut costs = vec![Cost::MAX; cells];
+    let goal = index(maze.goal());
+    costs[goal] = 0;
+    let mut queue = BinaryHeap::from([Reverse((0, goal))]);
+    while let Some(Reverse((cost, j))) = queue.pop() {
+        if cost > costs[j] {
+            continue;
+        }
+
+        for &(i, weight) in &parents[offsets[j]..offsets[j + 1]] {
+            if cost + weight < costs[i] {
+                costs[i] = cost + weight;
+                queue.push(Reverse((costs[i], i)));
+            }
+        }
+    }
+
+    for &i in &free {
+        let cost = costs[i];
+        if cost == Cost::MAX {
+            continue;
+        }
+        report.reachable += 1;
+
+        let cell = position(i);
+        let h = heuristic.compute_heuristic(cell);
+        if h > cost {
+            report.overestimates.push(Overestimate {
+                cell,
+                heuristic: h,
+                cost,
+            });
+        }
+    }
+
+    report
+}
@@ -0,0 +1,314 @@
+use std::{cmp::Reverse, collections::BinaryHeap, sync::Arc};
+
+use crate::{
+    cost::Cost,
+    generator::{ChildrenGenerator, DiagonalMovement, EightNeighborGenerator},
+    maze::Maze,
+    position::Position,
+};
+
+use super::{MazeHeuristic, OctileHeuristic};
+
+/// Stored distance of the cells that cannot be reached from a landmark.
+const UNREACHABLE: Cost = Cost::MAX;
+
+/// Where the landmarks of [`Landmarks`] are placed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LandmarkSelection {
+    /// Each landmark is the cell farthest from the ones already chosen, the first one being
+    /// the farthest from the start. Only the cells reachable from the start are picked.
+    #[default]
+    Farthest,
+    /// The free cells nearest to points spread evenly along the border of the maze, starting
+    /// from its top-left corner.
+    Corners,
+}
+
+/// Exact distances from a few landmark cells to every cell of a maze, for
+/// [`LandmarkHeuristic`].
+///
+/// Distances are found with Dijkstra over the moves allowed by a [`DiagonalMovement`], and
+/// are missing for the cells that cannot be reached from a landmark.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Landmarks {
+    width: u32,
+    height: u32,
+    movement: DiagonalMovement,
+    selection: LandmarkSelection,
+    count: usize,
+    fingerprint: u64,
+    landmarks: Vec<Position>,
+    distances: Vec<Cost>,
+}
+
+impl Landmarks {
+    /// Picks up to `count` landmarks, fewer if there are not enough cells to pick from.
+    pub fn new(
+        maze: &Maze,
+        count: usize,
+        selection: LandmarkSelection,
+        movement: DiagonalMovement,
+    ) -> Self {
+        let generator = EightNeighborGenerator::with_movement(maze, movement);
+        let mut landmarks = Vec::new();
+        let mut distances = Vec::new();
+
+        match selection {
+            LandmarkSelection::Farthest => {
+                let mut nearest = distance_field(maze, &generator, maze.start());
+
+                while landmarks.len() < count {
+                    let farthest = nearest
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, d)| **d != UNREACHABLE && **d > 0)
+                        .max_by_key(|(_, d)| **d);
+                    let Some((i, _)) = farthest else {
+                        break;
+                    };
+
+                    let landmark = position(maze, i);
+                    let field = distance_field(maze, &generator, landmark);
+                    if landmarks.is_empty() {
+                        nearest.clone_from(&field);
+                    } else {
+                        nearest
+                            .iter_mut()
+                            .zip(&field)
+                            .for_each(|(n, &d)| *n = (*n).min(d));
+                    }
+                    landmarks.push(landmark);
+                    distances.extend(field);
+                }
+            }
+            LandmarkSelection::Corners => {
+                for point in border_points(maze, count) {
+                    if let Some(landmark) = nearest_free(maze, point)
+                        && !landmarks.contains(&landmark)
+                    {
+                        landmarks.push(landmark);
+                        distances.extend(distance_field(maze, &generator, landmark));
+                    }
+                }
+            }
+        }
+
+        Landmarks {
+            width: maze.width(),
+            height: maze.height(),
+            movement,
+            selection,
+            count,
+            fingerprint: maze.fingerprint(),
+            landmarks,
+            distances,
+        }
+    }
+
+    /// Rebuilds the landmarks from the values returned by the accessors of saved ones.
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_parts(
+        width: u32,
+        height: u32,
+        movement: DiagonalMovement,
+        selection: LandmarkSelection,
+        count: usize,
+        fingerprint: u64,
+        landmarks: Vec<Position>,
+        distances: Vec<Cost>,
+    ) -> Result<Self, String> {
+        if landmarks.len() > count {
+            return Err(format!(
+                "Invalid landmarks: {} landmarks, at most {} asked for",
+                landmarks.len(),
+                count
+            ));
+        }
+        let expected = landmarks.len() * width as usize * height as usize;
+        if distances.len() != expected {
+            return Err(format!(
+                "Invalid landmarks: {} distances, expected {}",
+                distances.len(),
+                expected
+            ));
+        }
+        if let Some(landmark) = landmarks
+            .iter()
+            .find(|l| !(0..width as i32).contains(&l.x) || !(0..height as i32).contains(&l.y))
+        {
+            return Err(format!("Invalid landmark {landmark}"));
+        }
+
+        Ok(Landmarks {
+            width,
+            height,
+            movement,
+            selection,
+            count,
+            fingerprint,
+            landmarks,
+            distances,
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn movement(&self) -> DiagonalMovement {
+        self.movement
+    }
+
+    pub fn selection(&self) -> LandmarkSelection {
+        self.selection
+    }
+
+    /// Number of landmarks asked for, more than [`Self::landmarks`] if there were not enough
+    /// cells to pick from.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Hash of the walls and costs of the maze the landmarks were computed for.
+    pub fn fingerprint(&self) -> u64 {
+        self.fingerprint
+    }
+
+    pub fn landmarks(&self) -> &[Position] {
+        &self.landmarks
+    }
+
+    /// All the distances, landmark after landmark, each in row-major order, `Cost::MAX` for
+    /// the cells that cannot be reached.
+    pub fn distances(&self) -> &[Cost] {
+        &self.distances
+    }
+
+    /// Whether the landmarks were computed for a maze with the same walls and costs as `maze`.
+    pub fn matches(&self, maze: &Maze) -> bool {
+        self.width == maze.width()
+            && self.height == maze.height()
+            && self.fingerprint == maze.fingerprint()
+    }
+
+    /// Distance between the `landmark`-th landmark and `pos`, if it can be reached.
+    pub fn distance(&self, landmark: usize, pos: Position) -> Option<Cost> {
+        let cells = self.width as usize * self.height as usize;
+        let index = pos.y as usize * self.width as usize + pos.x as usize;
+        let distance = self.distances[landmark * cells + index];
+        (distance != UNREACHABLE).then_some(distance)
+    }
+}
+
+/// ALT heuristic: the best lower bound on the distance to the goal given by the triangle
+/// inequality over the distances to each landmark, or the octile distance if higher.
+///
+/// Admissible and consistent for the moves the [`Landmarks`] were computed with, but not for
+/// any-angle searches, whose paths can be shorter.
+pub struct LandmarkHeuristic {
+    landmarks: Arc<Landmarks>,
+    /// Distance between each landmark and the goal.
+    goal_distances: Vec<Option<Cost>>,
+    octile: OctileHeuristic,
+}
+
+impl LandmarkHeuristic {
+    pub fn new(maze: &Maze, landmarks: Arc<Landmarks>) -> Self {
+        assert!(
+            landmarks.width == maze.width() && landmarks.height == maze.height(),
+            "landmarks computed for another maze"
+        );
+
+        let goal_distances = (0..landmarks.landmarks.len())
+            .map(|l| landmarks.distance(l, maze.goal()))
+            .collect();
+
+        LandmarkHeuristic {
+            goal_distances,
+            octile: OctileHeuristic::with_movement(maze, landmarks.movement),
+            landmarks,
+        }
+    }
+}
+
+impl MazeHeuristic for LandmarkHeuristic {
+    fn compute_heuristic(&self, node: Position) -> Cost {
+        let octile = self.octile.compute_heuristic(node);
+
+        self.goal_distances
+            .iter()
+            .enumerate()
+            .filter_map(|(l, &to_goal)| {
+                let to_node = self.landmarks.distance(l, node)?;
+                Some(to_node.abs_diff(to_goal?))
+            })
+            .fold(octile, Cost::max)
+    }
+}
+
+fn cells(maze: &Maze) -> usize {
+    maze.width() as usize * maze.height() as usize
+}
+
+fn position(maze: &Maze, index: usize) -> Position {
+    let width = maze.width() as usize;
+    Position::new((index % width) as i32, (index / width) as i32)
+}
+
+/// Distance from `from` to every cell of `maze`, moving as `generator` does.
+fn distance_field(maze: &Maze, generator: &EightNeighborGenerator, from: Position) -> Vec<Cost> {
+    let index = |pos: Position| pos.y as usize * maze.width() as usize + pos.x as usize;
+
+    let mut distances = vec![UNREACHABLE; cells(maze)];
+    distances[index(from)] = 0;
+    let mut queue = BinaryHeap::from([Reverse((0, index(from)))]);
+
+    while let Some(Reverse((distance, i))) = queue.pop() {
+        if distance > distances[i] {
+            continue;
+        }
+
+        for child in generator.generate_children(position(maze, i), None) {
+            let j = index(child.node);
+            let new_distance = distance + child.weight;
+            if new_distance < distances[j] {
+                distances[j] = new_distance;
+                queue.push(Reverse((new_distance, j)));
+            }
+        }
+    }
+
+    distances
+}
+
+/// `count` points evenly spaced along the border of the maze, clockwise from the top-left
+/// corner.
+fn border_points(maze: &Maze, count: usize) -> impl Iterator<Item = Position> {
+    let (w, h) = (maze.width() as i64 - 1, maze.height() as i64 - 1);
+    let perimeter = 2 * (w + h);
+
+    (0..count as i64).map(move |i| {
+        let t = i * perimeter / count as i64;
+        let (x, y) = if t < w {
+            (t, 0)
+        } else if t < w + h {
+            (w, t - w)
+        } else if t < 2 * w + h {
+            (2 * w + h - t, h)
+        } else {
+            (0, perimeter - t)
+        };
+        Position::new(x as i32, y as i32)
+    })
+}
+
+fn nearest_free(maze: &Maze, point: Position) -> Option<Position> {
+    (0..cells(maze))
+        .map(|i| position(maze, i))
+        .filter(|&p| maze.is_free(p))
+        .min_by_key(|&p| (p - point).length_squared())
+}
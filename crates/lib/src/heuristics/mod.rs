@@ -0,0 +1,209 @@
+mod check;
+mod landmarks;
+
+use std::f64::consts::SQRT_2;
+
+use crate::{
+    cost::{COST_SCALE, Cost, DIAGONAL_COST, scale_cost},
+    generator::DiagonalMovement,
+    maze::Maze,
+    position::Position,
+};
+
+pub use check::{HeuristicReport, Inconsistency, Overestimate, check_heuristic};
+pub use landmarks::{LandmarkHeuristic, LandmarkSelection, Landmarks};
+
+pub trait MazeHeuristic {
+    fn compute_heuristic(&self, node: Position) -> Cost;
+}
+
+impl<H: MazeHeuristic + ?Sized> MazeHeuristic for Box<H> {
+    fn compute_heuristic(&self, node: Position) -> Cost {
+        self.as_ref().compute_heuristic(node)
+    }
+}
+
+/// Factor applied by [`DiagonalHeuristic`] unless told otherwise.
+pub const DEFAULT_TIE_BREAK: f64 = 1.001;
+
+/// [`OctileHeuristic`] scaled by a tie-break factor, [`DEFAULT_TIE_BREAK`] by default.
+///
+/// A factor slightly above 1 makes the search prefer the nodes nearer to the goal among the
+/// ones with the same cost, expanding fewer nodes, but the heuristic is no longer admissible:
+/// paths can cost up to that factor more than the optimal ones. A factor of 1 gives back the
+/// exact octile distance.
+pub struct DiagonalHeuristic {
+    octile: OctileHeuristic,
+    tie_break: f64,
+}
+
+impl DiagonalHeuristic {
+    pub fn new(maze: &Maze) -> Self {
+        DiagonalHeuristic::with_movement(maze, DiagonalMovement::default())
+    }
+
+    /// Without diagonal moves a diagonal costs two orthogonal steps, giving the Manhattan
+    /// distance.
+    pub fn with_movement(maze: &Maze, movement: DiagonalMovement) -> Self {
+        DiagonalHeuristic {
+            octile: OctileHeuristic::with_movement(maze, movement),
+            tie_break: DEFAULT_TIE_BREAK,
+        }
+    }
+
+    pub fn with_tie_break(self, tie_break: f64) -> Self {
+        DiagonalHeuristic { tie_break, ..self }
+    }
+}
+
+impl Default for DiagonalHeuristic {
+    fn default() -> Self {
+        DiagonalHeuristic {
+            octile: OctileHeuristic::default(),
+            tie_break: DEFAULT_TIE_BREAK,
+        }
+    }
+}
+
+impl MazeHeuristic for DiagonalHeuristic {
+    fn compute_heuristic(&self, node: Position) -> Cost {
+        scale_cost(self.octile.compute_heuristic(node), self.tie_break)
+    }
+}
+
+/// Cost of the cheapest path to the goal in a maze without walls where every cell costs as
+/// the cheapest one: diagonal moves cost `sqrt(2)`, or two orthogonal ones without them.
+pub struct OctileHeuristic {
+    goal: Position,
+    step_cost: Cost,
+    diagonal_cost: Cost,
+}
+
+impl OctileHeuristic {
+    pub fn new(maze: &Maze) -> Self {
+        OctileHeuristic::with_movement(maze, DiagonalMovement::default())
+    }
+
+    pub fn with_movement(maze: &Maze, movement: DiagonalMovement) -> Self {
+        let step_cost = scale_cost(COST_SCALE, maze.min_cost());
+        OctileHeuristic {
+            goal: maze.goal(),
+            step_cost,
+            diagonal_cost: match movement {
+                DiagonalMovement::Never => 2 * step_cost,
+                _ => scale_cost(DIAGONAL_COST, maze.min_cost()),
+            },
+        }
+    }
+}
+
+impl Default for OctileHeuristic {
+    fn default() -> Self {
+        OctileHeuristic {
+            goal: Position::default(),
+            step_cost: COST_SCALE,
+            diagonal_cost: DIAGONAL_COST,
+        }
+    }
+}
+
+impl MazeHeuristic for OctileHeuristic {
+    fn compute_heuristic(&self, node: Position) -> Cost {
+        let diff = (node - self.goal).abs();
+
+        let min = diff.min_element() as Cost;
+        let max = diff.max_element() as Cost;
+
+        min * self.diagonal_cost + (max - min) * self.step_cost
+    }
+}
+
+/// Sum of the horizontal and vertical distances to the goal, admissible only without
+/// diagonal moves.
+pub struct ManhattanHeuristic {
+    goal: Position,
+    step_cost: Cost,
+}
+
+impl ManhattanHeuristic {
+    pub fn new(maze: &Maze) -> Self {
+        ManhattanHeuristic {
+            goal: maze.goal(),
+            step_cost: scale_cost(COST_SCALE, maze.min_cost()),
+        }
+    }
+}
+
+impl MazeHeuristic for ManhattanHeuristic {
+    fn compute_heuristic(&self, node: Position) -> Cost {
+        (node - self.goal).abs().element_sum() as Cost * self.step_cost
+    }
+}
+
+/// Straight-line distance to the goal, admissible for any-angle paths too.
+pub struct EuclideanHeuristic {
+    goal: Position,
+    /// Lower bound on the cost of a path per cell of straight-line length.
+    unit_cost: f64,
+}
+
+impl EuclideanHeuristic {
+    pub fn new(maze: &Maze) -> Self {
+        let min_cost = maze.min_cost();
+        // diagonal steps are slightly shorter than `sqrt(2)` once rounded, and each move, at
+        // least one cell long, loses up to half a unit to each of its two roundings
+        let unit_cost = f64::min(COST_SCALE as f64, DIAGONAL_COST as f64 / SQRT_2) * min_cost
+            - (min_cost + 1.0) / 2.0;
+        EuclideanHeuristic {
+            goal: maze.goal(),
+            unit_cost: unit_cost.max(0.0),
+        }
+    }
+}
+
+impl MazeHeuristic for EuclideanHeuristic {
+    fn compute_heuristic(&self, node: Position) -> Cost {
+        ((node - self.goal).as_dvec2().length() * self.unit_cost).floor() as Cost
+    }
+}
+
+/// Largest of the horizontal and vertical distances to the goal, as if diagonal moves cost
+/// as orthogonal ones.
+pub struct ChebyshevHeuristic {
+    goal: Position,
+    step_cost: Cost,
+}
+
+impl ChebyshevHeuristic {
+    pub fn new(maze: &Maze) -> Self {
+        ChebyshevHeuristic {
+            goal: maze.goal(),
+            step_cost: scale_cost(COST_SCALE, maze.min_cost()),
+        }
+    }
+}
+
+impl MazeHeuristic for ChebyshevHeuristic {
+    fn compute_heuristic(&self, node: Position) -> Cost {
+        (node - self.goal).abs().max_element() as Cost * self.step_cost
+    }
+}
+
+/// Another heuristic multiplied by `weight`. A weight above 1 trades optimality for speed:
+/// with an admissible heuristic, paths cost at most `weight` times the optimal ones.
+pub struct WeightedHeuristic<H> {
+    heuristic: H,
+    weight: f64,
+}
+
+impl<H: MazeHeuristic> WeightedHeuristic<H> {
+    pub fn new(heuristic: H, weight: f64) -> Self {
+        WeightedHeuristic { heuristic, weight }
+    }
+}
+
+impl<H: MazeHeuristic> MazeHeuristic for WeightedHeuristic<H> {
+    fn compute_heuristic(&self, node: Position) -> Cost {
+        scale_cost(self.heuristic.compute_heuristic(node), self.weight)
+    }
+}
@@ -1,10 +1,13 @@
 pub mod algorithm;
+pub mod bitgrid;
 pub mod channel;
+pub mod cost;
 pub mod generator;
 pub mod heuristics;
 pub mod maze;
 pub mod maze_builder;
 pub mod position;
+pub mod subgoal;
 
 #[cfg(test)]
 mod tests;
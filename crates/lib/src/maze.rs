@@ -1,9 +1,15 @@
-use std::collections::HashSet;
+use itertools::Itertools;
 
-use crate::position::{Position, PositionUnit};
+use crate::{
+    bitgrid::BitGrid,
+    cost::{Cost, scale_cost, segment_length},
+    generator::DiagonalMovement,
+    position::{Position, PositionUnit, line},
+};
 
 pub struct Maze {
-    walls: HashSet<Position>,
+    walls: BitGrid,
+    costs: Option<Vec<f32>>,
     w: u32,
     h: u32,
     start: Position,
@@ -17,7 +23,8 @@ impl Maze {
             h: height,
             goal,
             start,
-            walls: HashSet::new(),
+            walls: BitGrid::new(width, height),
+            costs: None,
         }
     }
 
@@ -38,31 +45,155 @@ impl Maze {
     }
 
     pub fn valid(&self, pos: Position) -> bool {
-        pos.x < self.w as PositionUnit && pos.y < self.h as PositionUnit && pos.x >= 0 && pos.y >= 0
+        u32::try_from(pos.x).is_ok_and(|x| x < self.w)
+            && u32::try_from(pos.y).is_ok_and(|y| y < self.h)
     }
 
     pub fn set(&mut self, pos: Position, wall: bool) -> Result<(), String> {
         if self.valid(pos) {
-            if wall {
-                self.walls.insert(pos);
-            } else {
-                self.walls.remove(&pos);
-            }
+            self.walls.set(pos.x as u32, pos.y as u32, wall);
             Ok(())
         } else {
             Err(format!("Invalid position provided: {}", pos))
         }
     }
 
+    /// Moves the goal, to run several searches towards different cells of the same maze.
+    pub fn set_goal(&mut self, goal: Position) -> Result<(), String> {
+        if self.valid(goal) {
+            self.goal = goal;
+            Ok(())
+        } else {
+            Err(format!("Invalid position provided: {}", goal))
+        }
+    }
+
+    /// Sets the traversal cost multiplier of a cell. Every cell starts with a multiplier of 1.
+    pub fn set_cost(&mut self, pos: Position, cost: f64) -> Result<(), String> {
+        if !self.valid(pos) {
+            return Err(format!("Invalid position provided: {}", pos));
+        }
+        if !cost.is_finite() || cost <= 0.0 {
+            return Err(format!("Invalid cost provided for {}: {}", pos, cost));
+        }
+
+        let index = self.index(pos);
+        let len = self.w as usize * self.h as usize;
+        self.costs.get_or_insert_with(|| vec![1.0; len])[index] = cost as f32;
+        Ok(())
+    }
+
+    /// Traversal cost multiplier of a cell, 1 for cells that were never assigned one.
+    pub fn cost(&self, pos: Position) -> f64 {
+        match &self.costs {
+            Some(costs) if self.valid(pos) => costs[self.index(pos)] as f64,
+            _ => 1.0,
+        }
+    }
+
+    /// Whether no cell has ever been assigned a cost, so every move costs its length.
+    pub fn has_uniform_costs(&self) -> bool {
+        self.costs.is_none()
+    }
+
+    /// Lowest cost multiplier among the free cells, used to keep heuristics admissible.
+    pub fn min_cost(&self) -> f64 {
+        let Some(costs) = &self.costs else {
+            return 1.0;
+        };
+
+        let min = (0..self.h)
+            .flat_map(|y| (0..self.w).map(move |x| (x, y)))
+            .zip(costs)
+            .filter(|((x, y), _)| !self.walls.get(*x, *y))
+            .map(|(_, &c)| c as f64)
+            .fold(f64::INFINITY, f64::min);
+
+        if min.is_finite() { min } else { 1.0 }
+    }
+
+    /// Cost of a move between two adjacent cells: the length of the move times the mean
+    /// of the two cells' multipliers, so moving back and forth costs the same.
+    pub fn step_cost(&self, from: Position, to: Position) -> Cost {
+        let len = segment_length(to - from);
+        if self.has_uniform_costs() {
+            len
+        } else {
+            scale_cost(len, (self.cost(from) + self.cost(to)) / 2.0)
+        }
+    }
+
+    /// FNV-1a hash of the walls and costs, telling whether data precomputed for another
+    /// maze still applies to this one. Start and goal are left out.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hash = 0xcbf2_9ce4_8422_2325u64;
+        let mut feed = |value: u64| {
+            for byte in value.to_le_bytes() {
+                hash = (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3);
+            }
+        };
+
+        feed(self.w as u64);
+        feed(self.h as u64);
+        for y in 0..self.h {
+            self.walls.row(y).iter().for_each(|&w| feed(w));
+        }
+        if let Some(costs) = &self.costs {
+            costs.iter().for_each(|c| feed(c.to_bits() as u64));
+        }
+
+        hash
+    }
+
+    /// Whether the segment between the centres of `from` and `to` only crosses free cells,
+    /// with every diagonal step along it allowed by `movement`.
+    pub fn line_of_sight(&self, from: Position, to: Position, movement: DiagonalMovement) -> bool {
+        self.is_free(from)
+            && line(from, to)
+                .into_iter()
+                .tuple_windows()
+                .all(|(a, b)| self.is_free(b) && movement.allows(self, a, b - a))
+    }
+
+    /// Cost of a straight move between any two cells, ignoring walls: the length of the
+    /// segment times the mean multiplier of the cells it crosses. Matches
+    /// [`Maze::step_cost`] for adjacent cells.
+    pub fn line_cost(&self, from: Position, to: Position) -> Cost {
+        let len = segment_length(to - from);
+        if self.has_uniform_costs() {
+            return len;
+        }
+
+        let cells = line(from, to);
+        let total: f64 = cells.iter().map(|&p| self.cost(p)).sum();
+        scale_cost(len, total / cells.len() as f64)
+    }
+
     pub fn is_free(&self, pos: Position) -> bool {
         self.valid(pos) && !self.is_wall(pos)
     }
 
     pub fn is_wall(&self, pos: Position) -> bool {
-        self.valid(pos) && self.walls.contains(&pos)
+        self.valid(pos) && self.walls.get(pos.x as u32, pos.y as u32)
     }
 
+    /// Iterates over the walls in row-major order.
     pub fn walls(&self) -> impl Iterator<Item = Position> + '_ {
-        self.walls.iter().copied()
+        self.walls
+            .ones()
+            .map(|(x, y)| Position::new(x as PositionUnit, y as PositionUnit))
+    }
+
+    /// Wall bits of row `y`, packed as described in [`BitGrid`].
+    pub fn wall_row(&self, y: u32) -> &[u64] {
+        self.walls.row(y)
+    }
+
+    pub fn wall_grid(&self) -> &BitGrid {
+        &self.walls
+    }
+
+    fn index(&self, pos: Position) -> usize {
+        pos.y as usize * self.w as usize + pos.x as usize
     }
 }
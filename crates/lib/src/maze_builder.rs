@@ -1,12 +1,12 @@
 use crate::maze::Maze;
 use crate::position::Position;
 use anyhow::{Result, bail};
-use std::collections::HashSet;
 use std::fmt::Debug;
 
 #[derive(Default)]
 pub struct MazeBuilder {
-    walls: HashSet<Position>,
+    walls: Vec<Position>,
+    costs: Vec<(Position, f64)>,
     start: Option<Position>,
     goal: Option<Position>,
     errors: Vec<String>,
@@ -36,7 +36,12 @@ impl MazeBuilder {
     set_or_error!(goal, Position);
 
     pub fn add_wall(mut self, pos: Position) -> Self {
-        self.walls.insert(pos);
+        self.walls.push(pos);
+        self
+    }
+
+    pub fn add_cost(mut self, pos: Position, cost: f64) -> Self {
+        self.costs.push((pos, cost));
         self
     }
 
@@ -59,6 +64,12 @@ impl MazeBuilder {
                 }
             }
 
+            for (pos, cost) in self.costs {
+                if let Err(s) = maze.set_cost(pos, cost) {
+                    self.errors.push(format!("Invalid cost {}, {}", pos, s));
+                }
+            }
+
             maze_opt = Some(maze);
         }
 
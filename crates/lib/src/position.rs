@@ -1,8 +1,8 @@
 use std::ops::Index;
 
-use glam::I16Vec2;
+use glam::IVec2;
 
-pub type Position = I16Vec2;
+pub type Position = IVec2;
 pub type PositionUnit = <Position as Index<usize>>::Output;
 
 pub trait PosFunctions: Sized {
@@ -70,7 +70,7 @@ impl PosFunctions for Position {
     }
 
     fn norm(&self) -> f64 {
-        (self.length_squared() as f64).sqrt()
+        (self.x as f64).hypot(self.y as f64)
     }
 
     fn up(&self) -> Self {
@@ -89,3 +89,36 @@ impl PosFunctions for Position {
         *self + Position::X
     }
 }
+
+/// Cells crossed by the segment between the centres of `from` and `to`, in order.
+///
+/// Consecutive cells share a side, except where the segment passes exactly through a corner:
+/// there the walk takes a diagonal step.
+pub fn line(from: Position, to: Position) -> Vec<Position> {
+    let diff = to - from;
+    let steps = diff.signum();
+    let (nx, ny) = (diff.x.unsigned_abs() as i64, diff.y.unsigned_abs() as i64);
+
+    let mut cells = Vec::with_capacity((nx + ny + 1) as usize);
+    let mut current = from;
+    let (mut ix, mut iy) = (0, 0);
+    cells.push(current);
+
+    while ix < nx || iy < ny {
+        let decision = (1 + 2 * ix) * ny - (1 + 2 * iy) * nx;
+        if decision == 0 {
+            current += steps;
+            ix += 1;
+            iy += 1;
+        } else if decision < 0 {
+            current.x += steps.x;
+            ix += 1;
+        } else {
+            current.y += steps.y;
+            iy += 1;
+        }
+        cells.push(current);
+    }
+
+    cells
+}
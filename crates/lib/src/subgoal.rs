@@ -0,0 +1,367 @@
+use std::{
+    collections::{HashMap, HashSet},
+    iter::repeat_n,
+};
+
+use itertools::Itertools;
+
+use crate::{
+    algorithm::{AStar, Child, Info, Message, SearchAlgorithm, SearchLimits, StopReason},
+    channel::ChannelSender,
+    cost::{COST_SCALE, Cost, DIAGONAL_COST},
+    generator::{ChildrenGenerator, DiagonalMovement, MazePathRef, NodeIndex, PathInfo},
+    heuristics::MazeHeuristic,
+    maze::Maze,
+    position::{PosFunctions, Position},
+};
+
+const CARDINALS: [Position; 4] = [Position::NEG_Y, Position::Y, Position::NEG_X, Position::X];
+const DIAGONALS: [Position; 4] = [
+    Position::new(-1, -1),
+    Position::new(1, -1),
+    Position::new(-1, 1),
+    Position::new(1, 1),
+];
+
+/// Simple subgoal graph: the free cells at the convex corners of obstacles, linked when one
+/// is directly h-reachable from the other, that is reachable along an octile shortest path
+/// not passing by any other subgoal.
+///
+/// Moves follow [`DiagonalMovement::IfNoObstacles`] and every cell must cost the same, so
+/// that the octile distance is the cost of the edges. Searching the graph with start and
+/// goal linked to it gives optimal paths, expanding only subgoals.
+pub struct SubgoalGraph<'a> {
+    maze: &'a Maze,
+    subgoals: Vec<Position>,
+    index: HashMap<Position, usize>,
+    edges: Vec<Vec<usize>>,
+    stops: Stops,
+}
+
+impl<'a> SubgoalGraph<'a> {
+    pub fn new(maze: &'a Maze) -> Result<Self, String> {
+        if !maze.has_uniform_costs() {
+            return Err("Subgoal graphs need uniform costs".to_string());
+        }
+
+        let subgoals: Vec<_> = (0..maze.height() as i32)
+            .flat_map(|y| (0..maze.width() as i32).map(move |x| Position::new(x, y)))
+            .filter(|&pos| is_corner(maze, pos))
+            .collect();
+        let index: HashMap<_, _> = subgoals.iter().enumerate().map(|(i, &p)| (p, i)).collect();
+
+        let mut graph = SubgoalGraph {
+            maze,
+            stops: Stops::new(maze, &subgoals),
+            subgoals,
+            index,
+            edges: Vec::new(),
+        };
+
+        let mut edges = vec![HashSet::new(); graph.subgoals.len()];
+        for (i, &subgoal) in graph.subgoals.iter().enumerate() {
+            for reached in graph.direct_h_reachable(subgoal, None) {
+                let j = graph.index[&reached];
+                edges[i].insert(j);
+                edges[j].insert(i);
+            }
+        }
+        graph.edges = edges
+            .into_iter()
+            .map(|e| e.into_iter().sorted().collect())
+            .collect();
+
+        Ok(graph)
+    }
+
+    pub fn subgoals(&self) -> &[Position] {
+        &self.subgoals
+    }
+
+    /// Number of edges, each counted once per direction.
+    pub fn edge_count(&self) -> usize {
+        self.edges.iter().map(Vec::len).sum()
+    }
+
+    fn allows(&self, from: Position, dir: Position) -> bool {
+        self.maze.is_free(from + dir)
+            && DiagonalMovement::IfNoObstacles.allows(self.maze, from, dir)
+    }
+
+    fn is_subgoal(&self, pos: Position) -> bool {
+        self.index.contains_key(&pos)
+    }
+
+    /// Subgoals, and `goal` if given, reachable from `from` along a path made of diagonal
+    /// moves followed by straight ones, without passing by another of them.
+    ///
+    /// Each diagonal is followed from `from`, scanning both of its components from every cell
+    /// crossed. A scan never goes further than the one of the previous cell, so that the
+    /// cells in between cannot hide an obstacle with a subgoal at its corner.
+    fn direct_h_reachable(&self, from: Position, goal: Option<Position>) -> Vec<Position> {
+        let mut reached = Vec::new();
+        // free steps before an obstacle or a target, adding the target
+        let clearance = |pos: Position, dir: Position, max: u32, reached: &mut Vec<_>| {
+            let (steps, target) = self.stops.clearance(self.maze, pos, dir, goal);
+            match target {
+                Some(target) if steps < max => {
+                    reached.push(target);
+                    steps
+                }
+                _ => steps.min(max),
+            }
+        };
+
+        for dir in CARDINALS {
+            clearance(from, dir, u32::MAX, &mut reached);
+        }
+
+        for dir in DIAGONALS {
+            let components = dir.components();
+            let mut max = components.map(|c| clearance(from, c, u32::MAX, &mut Vec::new()));
+            let mut cur = from;
+            while self.allows(cur, dir) {
+                cur += dir;
+                if Some(cur) == goal || self.is_subgoal(cur) {
+                    reached.push(cur);
+                    break;
+                }
+                for (c, max) in components.iter().zip(max.iter_mut()) {
+                    *max = clearance(cur, *c, *max, &mut reached);
+                }
+            }
+        }
+
+        reached
+    }
+
+    /// Cells along the octile path from `from` to `to` moving diagonally first, if free.
+    fn diagonal_first(&self, from: Position, to: Position) -> Option<Vec<Position>> {
+        let delta = to - from;
+        let diagonal_steps = delta.abs().min_element() as usize;
+        let straight_steps = delta.abs().max_element() as usize - diagonal_steps;
+        let diagonal = delta.signum();
+        let straight = (delta - diagonal * diagonal_steps as i32).signum();
+
+        let mut path = vec![from];
+        let mut cur = from;
+        for dir in repeat_n(diagonal, diagonal_steps).chain(repeat_n(straight, straight_steps)) {
+            if !self.allows(cur, dir) {
+                return None;
+            }
+            cur += dir;
+            path.push(cur);
+        }
+        Some(path)
+    }
+
+    /// Cells of an edge found by [`Self::direct_h_reachable`] from either of its ends.
+    fn refine(&self, from: Position, to: Position) -> Option<Vec<Position>> {
+        self.diagonal_first(from, to).or_else(|| {
+            self.diagonal_first(to, from).map(|mut path| {
+                path.reverse();
+                path
+            })
+        })
+    }
+}
+
+/// Walls and subgoals of every row and column, sorted, to find how far a straight move can
+/// go without scanning the cells in between.
+struct Stops {
+    rows: Vec<Vec<i32>>,
+    columns: Vec<Vec<i32>>,
+}
+
+impl Stops {
+    fn new(maze: &Maze, subgoals: &[Position]) -> Self {
+        let mut rows = vec![Vec::new(); maze.height() as usize];
+        let mut columns = vec![Vec::new(); maze.width() as usize];
+        let walls = maze
+            .wall_grid()
+            .ones()
+            .map(|(x, y)| Position::new(x as i32, y as i32));
+        for pos in walls.chain(subgoals.iter().copied()) {
+            rows[pos.y as usize].push(pos.x);
+            columns[pos.x as usize].push(pos.y);
+        }
+        for line in rows.iter_mut().chain(columns.iter_mut()) {
+            line.sort_unstable();
+        }
+        Stops { rows, columns }
+    }
+
+    /// Free steps from `pos` along the straight `dir` before an obstacle, a subgoal or
+    /// `goal`, with the subgoal or `goal` that ends them.
+    fn clearance(
+        &self,
+        maze: &Maze,
+        pos: Position,
+        dir: Position,
+        goal: Option<Position>,
+    ) -> (u32, Option<Position>) {
+        let (line, at, len) = if dir.y == 0 {
+            (&self.rows[pos.y as usize], pos.x, maze.width() as i32)
+        } else {
+            (&self.columns[pos.x as usize], pos.y, maze.height() as i32)
+        };
+        let forward = dir.x + dir.y > 0;
+
+        let stop = if forward {
+            line.get(line.partition_point(|&c| c <= at)).copied()
+        } else {
+            line.partition_point(|&c| c < at)
+                .checked_sub(1)
+                .map(|i| line[i])
+        };
+        // the cell just outside the maze if there is no stop
+        let edge = if forward { len } else { -1 };
+        let mut distance = (stop.unwrap_or(edge) - at).abs();
+        let mut target = stop
+            .map(|c| pos + dir * (c - at).abs())
+            .filter(|&p| maze.is_free(p));
+
+        if let Some(goal) = goal {
+            let to_goal = goal - pos;
+            let steps = to_goal.dot(dir);
+            if to_goal == dir * steps && (1..=distance).contains(&steps) {
+                distance = steps;
+                target = Some(goal);
+            }
+        }
+
+        ((distance - 1) as u32, target)
+    }
+}
+
+/// Whether `pos` is free and touches the corner of an obstacle that has to be walked around.
+fn is_corner(maze: &Maze, pos: Position) -> bool {
+    maze.is_free(pos)
+        && DIAGONALS.iter().any(|&d| {
+            !maze.is_free(pos + d) && d.components().iter().all(|&c| maze.is_free(pos + c))
+        })
+}
+
+/// The subgoal graph with start and goal linked to it.
+///
+/// Nodes are numbered by their index among the subgoals, then the start and the goal.
+struct QueryGraph<'g, 'a> {
+    graph: &'g SubgoalGraph<'a>,
+    start: Position,
+    goal: Position,
+    extra: HashMap<Position, Vec<Position>>,
+}
+
+impl ChildrenGenerator for QueryGraph<'_, '_> {
+    fn generate_children(&self, current: Position, _parent: Option<Position>) -> Vec<Child> {
+        let graph = self.graph;
+        graph
+            .index
+            .get(&current)
+            .map(|&i| &graph.edges[i])
+            .into_iter()
+            .flatten()
+            .map(|&j| graph.subgoals[j])
+            .chain(self.extra.get(&current).into_iter().flatten().copied())
+            .map(|node| Child::new(node, octile(current, node)))
+            .collect()
+    }
+
+    fn reconstruct_path(&self, path: MazePathRef) -> PathInfo {
+        let cost = path
+            .iter()
+            .tuple_windows()
+            .map(|(&a, &b)| octile(a, b))
+            .sum();
+        PathInfo::new(path.to_vec(), cost)
+    }
+
+    fn node_index(&self) -> Option<&dyn NodeIndex> {
+        Some(self)
+    }
+}
+
+impl NodeIndex for QueryGraph<'_, '_> {
+    fn node_count(&self) -> usize {
+        self.graph.subgoals.len() + 2
+    }
+
+    fn index_of(&self, node: Position) -> Option<usize> {
+        let count = self.graph.subgoals.len();
+        self.graph
+            .index
+            .get(&node)
+            .copied()
+            .or_else(|| (node == self.start).then_some(count))
+            .or_else(|| (node == self.goal).then_some(count + 1))
+    }
+
+    fn node_at(&self, index: usize) -> Position {
+        let subgoals = &self.graph.subgoals;
+        match index.checked_sub(subgoals.len()) {
+            None => subgoals[index],
+            Some(0) => self.start,
+            Some(_) => self.goal,
+        }
+    }
+}
+
+fn octile(from: Position, to: Position) -> Cost {
+    let delta = (to - from).abs();
+    let (min, max) = (delta.min_element(), delta.max_element());
+    min as Cost * DIAGONAL_COST + (max - min) as Cost * COST_SCALE
+}
+
+/// Exact octile distance to a goal, the cost of the shortest path without walls.
+struct OctileTo(Position);
+
+impl MazeHeuristic for OctileTo {
+    fn compute_heuristic(&self, node: Position) -> Cost {
+        octile(node, self.0)
+    }
+}
+
+/// Searches the graph with start and goal linked to the subgoals directly h-reachable
+/// from them, then walks each edge. Neither the generator nor the heuristic are used: the
+/// exact octile distance guides the search, as an inflated heuristic could miss the
+/// shortest path. The search stops with [`StopReason::Unrefinable`] if an edge cannot be
+/// walked.
+impl SearchAlgorithm for SubgoalGraph<'_> {
+    fn search_with_limits<G: ChildrenGenerator + ?Sized, C: ChannelSender<Message>>(
+        &self,
+        start: Position,
+        goal: Position,
+        _heuristic: &dyn MazeHeuristic,
+        _generator: &G,
+        channel: C,
+        limits: &SearchLimits,
+    ) -> Info {
+        let mut extra: HashMap<Position, Vec<Position>> = HashMap::new();
+        extra.insert(start, self.direct_h_reachable(start, Some(goal)));
+        for reached in self.direct_h_reachable(goal, None) {
+            extra.entry(reached).or_default().push(goal);
+        }
+
+        let query = QueryGraph {
+            graph: self,
+            start,
+            goal,
+            extra,
+        };
+        let heuristic = OctileTo(goal);
+        let mut info = AStar.search_with_limits(start, goal, &heuristic, &query, channel, limits);
+
+        if let Some(waypoints) = info.path.take() {
+            let mut path = vec![start];
+            for (&a, &b) in waypoints.path.iter().tuple_windows() {
+                let Some(edge) = self.refine(a, b) else {
+                    info.stop = StopReason::Unrefinable;
+                    return info;
+                };
+                path.extend_from_slice(&edge[1..]);
+            }
+            info.path = Some(PathInfo::new(path, waypoints.cost));
+        }
+        info
+    }
+}
@@ -1,6 +1,31 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use super::{Lcg, assert_same_cost, assert_valid_path, random_maze};
 use crate::{
-    algorithm::a_star, channel::noop_sender, generator::JpsGenerator,
-    heuristics::DiagonalHeuristic, maze_builder::MazeBuilder, position::Position,
+    algorithm::{
+        AStar, Abstraction, AraStar, BestFirst, BidirectionalAStar, BreadthFirst, BucketQueue,
+        Child, Dijkstra, Frontier, GreedyBestFirst, HpaStar, IdaStar, Info, LazyThetaStar, Message,
+        PriorityQueue, QueueKey, QueueKind, Search, SearchAlgorithm, SearchBuffers, SearchLimits,
+        SmaStar, Step, StopReason, ThetaStar, TieBreak, WeightedAStar, a_star,
+    },
+    channel::{channel, noop_sender},
+    cost::{COST_SCALE, Cost, DIAGONAL_COST},
+    generator::{
+        BitJpsGenerator, ChildrenGenerator, DiagonalMovement, EightNeighborGenerator,
+        FourNeighborGenerator, Jps4Generator, JpsGenerator, JpsPlusGenerator, JumpTable,
+        MazePathRef, PathInfo,
+    },
+    heuristics::{DiagonalHeuristic, MazeHeuristic, OctileHeuristic},
+    maze::Maze,
+    maze_builder::MazeBuilder,
+    position::Position,
+    subgoal::SubgoalGraph,
 };
 
 #[test]
@@ -26,14 +51,1097 @@ fn test_find_path() {
 
     assert_eq!(
         path.path,
-        &[
-            Position::new(0, 0),
-            Position::new(1, 1),
-            Position::new(9, 9)
-        ]
+        (0..10).map(|i| Position::new(i, i)).collect::<Vec<_>>()
     );
-    assert_eq!(path.cost, 1.0);
+    assert_eq!(path.cost, 9 * DIAGONAL_COST);
 
     assert!(info.max_length > 0);
     assert!(info.nodes > 0);
 }
+
+struct NoHeuristic;
+
+impl MazeHeuristic for NoHeuristic {
+    fn compute_heuristic(&self, _node: Position) -> Cost {
+        0
+    }
+}
+
+struct Unpruned<'a>(JpsGenerator<'a>);
+
+impl ChildrenGenerator for Unpruned<'_> {
+    fn generate_children(&self, current: Position, _parent: Option<Position>) -> Vec<Child> {
+        self.0.generate_children(current, None)
+    }
+
+    fn reconstruct_path(&self, path: MazePathRef) -> PathInfo {
+        self.0.reconstruct_path(path)
+    }
+}
+
+#[test]
+fn test_weighted_path_avoids_expensive_cells() {
+    let start = Position::new(0, 2);
+    let goal = Position::new(9, 2);
+    let mut builder = MazeBuilder::new()
+        .start(start)
+        .goal(goal)
+        .width(10)
+        .height(5);
+    for y in 1..4 {
+        builder = builder.add_cost(Position::new(5, y), 20.0);
+    }
+    let maze = builder.build().unwrap();
+
+    let generator = JpsGenerator::new(&maze);
+    let info = a_star(start, goal, &NoHeuristic, &generator, noop_sender());
+    let path = info.path.unwrap();
+
+    assert!(path.path.iter().all(|p| maze.cost(*p) == 1.0));
+    assert_eq!(path.cost, 5 * COST_SCALE + 4 * DIAGONAL_COST);
+}
+
+#[test]
+fn test_weighted_jps_is_optimal() {
+    let mut rng = Lcg::new(12345);
+
+    for _ in 0..20 {
+        let start = Position::new(0, 0);
+        let goal = Position::new(19, 19);
+        let mut maze = random_maze(&mut rng, (20, 20), start, goal, 0, 25);
+        // isolated pillars, so that no path has to squeeze between two walls
+        for x in (0..20).step_by(2) {
+            for y in (0..20).step_by(2) {
+                let pos = Position::new(x, y);
+                if pos != start && rng.next() < 40 {
+                    maze.set(pos, true).unwrap();
+                }
+            }
+        }
+
+        let jps = a_star(
+            start,
+            goal,
+            &NoHeuristic,
+            &JpsGenerator::new(&maze),
+            noop_sender(),
+        );
+        let plain = a_star(
+            start,
+            goal,
+            &NoHeuristic,
+            &Unpruned(JpsGenerator::new(&maze)),
+            noop_sender(),
+        );
+
+        assert_same_cost(&jps.path, &plain.path);
+    }
+}
+
+#[test]
+fn test_jps4_is_optimal() {
+    let mut rng = Lcg::new(54321);
+
+    for round in 0..40 {
+        let start = Position::new(0, 0);
+        let goal = Position::new(24, 17);
+        let maze = random_maze(
+            &mut rng,
+            (25, 20),
+            start,
+            goal,
+            25,
+            if round % 2 == 1 { 15 } else { 0 },
+        );
+
+        let jps = a_star(
+            start,
+            goal,
+            &NoHeuristic,
+            &Jps4Generator::new(&maze),
+            noop_sender(),
+        );
+        let plain = a_star(
+            start,
+            goal,
+            &NoHeuristic,
+            &FourNeighborGenerator::new(&maze),
+            noop_sender(),
+        );
+
+        assert_same_cost(&jps.path, &plain.path);
+        if let Some(path) = jps.path {
+            assert_valid_path(&maze, DiagonalMovement::Never, &path);
+        }
+    }
+}
+
+#[test]
+fn test_jps_follows_diagonal_movement() {
+    let mut rng = Lcg::new(2024);
+
+    let movements = [
+        DiagonalMovement::Always,
+        DiagonalMovement::Never,
+        DiagonalMovement::IfNoObstacles,
+        DiagonalMovement::IfAtMostOneObstacle,
+    ];
+    for round in 0..80 {
+        let start = Position::new(0, 0);
+        let goal = Position::new(21, 18);
+        let maze = random_maze(
+            &mut rng,
+            (22, 19),
+            start,
+            goal,
+            30,
+            if round % 2 == 1 { 15 } else { 0 },
+        );
+
+        let movement = movements[round % movements.len()];
+        let jps = a_star(
+            start,
+            goal,
+            &NoHeuristic,
+            &JpsGenerator::with_movement(&maze, movement),
+            noop_sender(),
+        );
+        let plain = a_star(
+            start,
+            goal,
+            &NoHeuristic,
+            &EightNeighborGenerator::with_movement(&maze, movement),
+            noop_sender(),
+        );
+
+        assert_same_cost(&jps.path, &plain.path);
+        if let Some(path) = jps.path {
+            assert_valid_path(&maze, movement, &path);
+        }
+    }
+}
+
+#[test]
+fn test_jps_plus_matches_jps() {
+    let mut rng = Lcg::new(77);
+
+    let movements = [
+        DiagonalMovement::Always,
+        DiagonalMovement::IfNoObstacles,
+        DiagonalMovement::IfAtMostOneObstacle,
+        DiagonalMovement::Never,
+    ];
+    for round in 0..60 {
+        let (width, height) = (25, 17);
+        let start = rng.position(width, height);
+        let goal = rng.position(width, height);
+        let maze = random_maze(
+            &mut rng,
+            (width, height),
+            start,
+            goal,
+            25,
+            if round % 2 == 1 { 10 } else { 0 },
+        );
+
+        let movement = movements[round / 2 % movements.len()];
+        let table = JumpTable::new(&maze, movement);
+        assert!(table.matches(&maze));
+        let jps = JpsGenerator::with_movement(&maze, movement);
+        let jps_plus = JpsPlusGenerator::new(&maze, &table, movement).unwrap();
+
+        assert_same_children(&maze, &jps, &jps_plus);
+    }
+
+    let mut maze = Maze::new(20, 20, Position::ZERO, Position::new(19, 19));
+    let table = JumpTable::new(&maze, DiagonalMovement::default());
+    assert!(JpsPlusGenerator::new(&maze, &table, DiagonalMovement::Never).is_err());
+    maze.set(Position::new(10, 10), true).unwrap();
+    assert!(JpsPlusGenerator::new(&maze, &table, DiagonalMovement::default()).is_err());
+}
+
+#[test]
+fn test_bit_jps_matches_jps() {
+    let mut rng = Lcg::new(31);
+
+    let movements = [
+        DiagonalMovement::Always,
+        DiagonalMovement::IfNoObstacles,
+        DiagonalMovement::IfAtMostOneObstacle,
+    ];
+    for round in 0..30 {
+        // long runs cross word boundaries both by row and by column
+        let (width, height) = if round % 4 < 2 {
+            (64 + 3 * round as i32, 9)
+        } else {
+            (9, 64 + 3 * round as i32)
+        };
+        let start = rng.position(width, height);
+        let goal = Position::new(
+            (rng.next() * 7) as i32 % width,
+            (rng.next() * 7) as i32 % height,
+        );
+        let maze = random_maze(
+            &mut rng,
+            (width, height),
+            start,
+            goal,
+            12,
+            if round % 2 == 1 { 4 } else { 0 },
+        );
+
+        let movement = movements[round % movements.len()];
+        let jps = JpsGenerator::with_movement(&maze, movement);
+        let bit_jps = BitJpsGenerator::with_movement(&maze, movement);
+        assert_same_children(&maze, &jps, &bit_jps);
+    }
+}
+
+#[test]
+fn test_hpa_star() {
+    let mut rng = Lcg::new(5);
+
+    for round in 0..40 {
+        let (width, height) = (37, 29);
+        let start = rng.position(width, height);
+        let goal = rng.position(width, height);
+        let maze = random_maze(
+            &mut rng,
+            (width, height),
+            start,
+            goal,
+            25,
+            if round % 2 == 1 { 10 } else { 0 },
+        );
+
+        let movement = if round % 4 < 2 {
+            DiagonalMovement::IfAtMostOneObstacle
+        } else {
+            DiagonalMovement::Never
+        };
+        let heuristic = DiagonalHeuristic::with_movement(&maze, movement);
+        let generator = EightNeighborGenerator::with_movement(&maze, movement);
+        let optimal = a_star(start, goal, &NoHeuristic, &generator, noop_sender());
+
+        let abstraction = Abstraction::new(&maze, 8, movement);
+        let info = HpaStar::new(&maze, &abstraction).unwrap().search(
+            start,
+            goal,
+            &heuristic,
+            &generator,
+            noop_sender(),
+        );
+        assert_eq!(info.nodes, info.abstract_nodes + info.refined_nodes);
+
+        assert_eq!(info.path.is_some(), optimal.path.is_some());
+        if let (Some(path), Some(optimal)) = (info.path, optimal.path) {
+            assert!(path.cost >= optimal.cost);
+            assert_valid_path(&maze, movement, &path);
+        }
+    }
+
+    let mut maze = Maze::new(20, 20, Position::ZERO, Position::new(19, 19));
+    let abstraction = Abstraction::new(&maze, 8, DiagonalMovement::default());
+    maze.set(Position::new(10, 10), true).unwrap();
+    assert!(HpaStar::new(&maze, &abstraction).is_err());
+
+    // a stale abstraction passed off as the one of the maze, with edges through a wall
+    let mut maze = Maze::new(24, 8, Position::ZERO, Position::new(23, 7));
+    let abstraction = Abstraction::new(&maze, 8, DiagonalMovement::default());
+    for y in 0..8 {
+        maze.set(Position::new(12, y), true).unwrap();
+    }
+    let stale = Abstraction::from_parts(
+        24,
+        8,
+        8,
+        DiagonalMovement::default(),
+        maze.fingerprint(),
+        abstraction.nodes().to_vec(),
+        abstraction.edges().to_vec(),
+    )
+    .unwrap();
+    let info = HpaStar::new(&maze, &stale).unwrap().search(
+        maze.start(),
+        maze.goal(),
+        &OctileHeuristic::new(&maze),
+        &JpsGenerator::new(&maze),
+        noop_sender(),
+    );
+    assert!(info.path.is_none());
+    assert_eq!(info.stop, StopReason::Unrefinable);
+}
+
+#[test]
+fn test_subgoal_graph() {
+    let mut rng = Lcg::new(11);
+
+    for _ in 0..60 {
+        let (width, height) = (31, 23);
+        let start = rng.position(width, height);
+        let goal = rng.position(width, height);
+        let density = 10 + rng.next() % 30;
+        let maze = random_maze(&mut rng, (width, height), start, goal, density, 0);
+
+        let movement = DiagonalMovement::IfNoObstacles;
+        // inflated, which the subgoal search must not follow
+        let heuristic = DiagonalHeuristic::with_movement(&maze, movement).with_tie_break(3.0);
+        let generator = EightNeighborGenerator::with_movement(&maze, movement);
+        let optimal = a_star(start, goal, &NoHeuristic, &generator, noop_sender());
+
+        let graph = SubgoalGraph::new(&maze).unwrap();
+        let info = graph.search(start, goal, &heuristic, &generator, noop_sender());
+
+        assert_same_cost(&info.path, &optimal.path);
+        if let Some(path) = info.path {
+            assert_valid_path(&maze, movement, &path);
+        }
+    }
+
+    let weighted = MazeBuilder::new()
+        .start(Position::new(0, 0))
+        .goal(Position::new(4, 4))
+        .width(5)
+        .height(5)
+        .add_cost(Position::new(2, 2), 3.0)
+        .build()
+        .unwrap();
+    assert!(SubgoalGraph::new(&weighted).is_err());
+}
+
+/// Checks that `actual` generates the same children as `expected` for every free cell,
+/// coming from every neighbour or from nowhere.
+fn assert_same_children(
+    maze: &Maze,
+    expected: &impl ChildrenGenerator,
+    actual: &impl ChildrenGenerator,
+) {
+    let children = |generator: &dyn ChildrenGenerator, current, parent| -> Vec<_> {
+        generator
+            .generate_children(current, parent)
+            .into_iter()
+            .map(|c| (c.node, c.weight))
+            .collect()
+    };
+
+    for x in 0..maze.width() as i32 {
+        for y in 0..maze.height() as i32 {
+            let current = Position::new(x, y);
+            if maze.is_wall(current) {
+                continue;
+            }
+            for parent in (-1..=1).flat_map(|dx| (-1..=1).map(move |dy| Position::new(dx, dy))) {
+                let parent = (parent != Position::ZERO).then_some(current + parent);
+                assert_eq!(
+                    children(actual, current, parent),
+                    children(expected, current, parent),
+                    "{current} from {parent:?}"
+                );
+            }
+        }
+    }
+}
+
+fn walled_maze() -> Maze {
+    let mut builder = MazeBuilder::new()
+        .start(Position::new(0, 0))
+        .goal(Position::new(19, 0))
+        .width(20)
+        .height(20);
+    for x in [4, 12] {
+        for y in 0..17 {
+            builder = builder.add_wall(Position::new(x, y));
+        }
+    }
+    for y in 3..20 {
+        builder = builder.add_wall(Position::new(8, y));
+    }
+    builder.build().unwrap()
+}
+
+fn search_cost(algorithm: &impl SearchAlgorithm, maze: &Maze) -> Cost {
+    let heuristic = DiagonalHeuristic::new(maze);
+    let generator = JpsGenerator::new(maze);
+    let info = algorithm.search(
+        maze.start(),
+        maze.goal(),
+        &heuristic,
+        &generator,
+        noop_sender(),
+    );
+
+    assert!(info.nodes > 0);
+    assert_eq!(info.stop, StopReason::Found);
+    let path = info.path.unwrap();
+    assert_eq!(path.path.first(), Some(&maze.start()));
+    assert_eq!(path.path.last(), Some(&maze.goal()));
+    path.cost
+}
+
+#[test]
+fn test_search_algorithms() {
+    let maze = walled_maze();
+
+    let optimal = search_cost(&Dijkstra, &maze);
+    assert_eq!(search_cost(&AStar, &maze), optimal);
+
+    let weighted = search_cost(&WeightedAStar::new(2.0), &maze);
+    assert!(weighted >= optimal && weighted <= 2 * optimal);
+
+    assert!(search_cost(&GreedyBestFirst, &maze) >= optimal);
+    assert_eq!(search_cost(&BidirectionalAStar, &maze), optimal);
+    assert_eq!(search_cost(&IdaStar, &maze), optimal);
+    assert_eq!(search_cost(&SmaStar::new(1000), &maze), optimal);
+    assert_eq!(search_cost(&AraStar::new(3.0, 0.5), &maze), optimal);
+    assert!(search_cost(&BreadthFirst, &maze) >= optimal);
+}
+
+#[test]
+fn test_search_no_path() {
+    let maze = MazeBuilder::new()
+        .start(Position::new(0, 0))
+        .goal(Position::new(2, 0))
+        .width(3)
+        .height(1)
+        .add_wall(Position::new(1, 0))
+        .build()
+        .unwrap();
+    let heuristic = DiagonalHeuristic::new(&maze);
+    let generator = JpsGenerator::new(&maze);
+
+    let info = BreadthFirst.search(
+        maze.start(),
+        maze.goal(),
+        &heuristic,
+        &generator,
+        noop_sender(),
+    );
+    assert!(info.path.is_none());
+    assert_eq!(info.nodes, 1);
+    assert_eq!(info.stop, StopReason::Exhausted);
+}
+
+fn limited_search(algorithm: &impl SearchAlgorithm, maze: &Maze, limits: &SearchLimits) -> Info {
+    let heuristic = DiagonalHeuristic::new(maze);
+    let generator = EightNeighborGenerator::new(maze);
+    algorithm.search_with_limits(
+        maze.start(),
+        maze.goal(),
+        &heuristic,
+        &generator,
+        noop_sender(),
+        limits,
+    )
+}
+
+#[test]
+fn test_search_limits() {
+    let maze = walled_maze();
+    let theta = ThetaStar::new(&maze, DiagonalMovement::default(), false);
+
+    let limits = SearchLimits::default().with_max_expanded(20);
+    for info in [
+        limited_search(&AStar, &maze, &limits),
+        limited_search(&Dijkstra, &maze, &limits),
+        limited_search(&BreadthFirst, &maze, &limits),
+        limited_search(&BidirectionalAStar, &maze, &limits),
+        limited_search(&IdaStar, &maze, &limits),
+        limited_search(&SmaStar::new(1000), &maze, &limits),
+        limited_search(&AraStar::new(3.0, 0.5), &maze, &limits),
+        limited_search(&theta, &maze, &limits),
+    ] {
+        assert_eq!(info.stop, StopReason::MaxExpanded);
+        assert_eq!(info.nodes, 20);
+        assert!(info.path.is_none());
+    }
+
+    let info = limited_search(&AStar, &maze, &SearchLimits::default().with_max_queue(5));
+    assert_eq!(info.stop, StopReason::MaxQueue);
+    assert!(info.max_length <= 5);
+
+    let past = Instant::now() - Duration::from_millis(1);
+    let info = limited_search(&AStar, &maze, &SearchLimits::default().with_deadline(past));
+    assert_eq!(info.stop, StopReason::Deadline);
+    assert_eq!(info.nodes, 0);
+
+    let cancel = Arc::new(AtomicBool::new(false));
+    let limits = SearchLimits::default().with_cancel(cancel.clone());
+    assert_eq!(
+        limited_search(&AStar, &maze, &limits).stop,
+        StopReason::Found
+    );
+    cancel.store(true, Ordering::Relaxed);
+    assert_eq!(
+        limited_search(&AStar, &maze, &limits).stop,
+        StopReason::Cancelled
+    );
+
+    // ARA* keeps the best path found before running out of time
+    let limits = SearchLimits::default().with_max_expanded(240);
+    let info = limited_search(&AraStar::new(3.0, 0.1), &maze, &limits);
+    assert_eq!(info.stop, StopReason::MaxExpanded);
+    assert!(info.path.is_some());
+}
+
+#[test]
+fn test_search_step() {
+    let maze = walled_maze();
+    let heuristic = DiagonalHeuristic::new(&maze);
+    let generator = EightNeighborGenerator::new(&maze);
+    let (start, goal) = (maze.start(), maze.goal());
+    let expected = a_star(start, goal, &heuristic, &generator, noop_sender());
+
+    let mut search = Search::new(start, goal, &heuristic, &generator, noop_sender());
+    assert_eq!(search.peek(), Some(start));
+    assert_eq!(search.g(start), Some(0));
+    assert_eq!(search.f(start), Some(heuristic.compute_heuristic(start)));
+    assert_eq!(search.step(), Step::Expanded(start));
+    assert!(search.is_closed(start));
+    assert_eq!(search.g(Position::new(1, 1)), Some(DIAGONAL_COST));
+    assert_eq!(search.g(goal), None);
+
+    // pausing and resuming gives the same search as running it at once
+    let limits = SearchLimits::default().with_max_expanded(expected.nodes / 2);
+    assert_eq!(search.run_until(&limits), StopReason::MaxExpanded);
+    assert_eq!(search.info().nodes, expected.nodes / 2);
+    assert_eq!(search.finished(), None);
+
+    let stop = search.run_until(&SearchLimits::default());
+    assert_eq!(stop, StopReason::Found);
+    assert_eq!(search.step(), Step::Finished(StopReason::Found));
+    assert_eq!(search.g(goal), Some(expected.path.as_ref().unwrap().cost));
+
+    let info = search.into_info();
+    assert_eq!(info.nodes, expected.nodes);
+    assert_eq!(info.max_length, expected.max_length);
+    assert_eq!(info.path.unwrap().path, expected.path.unwrap().path);
+}
+
+#[test]
+fn test_search_reuses_buffers() {
+    let mazes = [
+        walled_maze(),
+        MazeBuilder::new()
+            .start(Position::new(4, 0))
+            .goal(Position::new(0, 6))
+            .width(5)
+            .height(7)
+            .add_wall(Position::new(2, 3))
+            .build()
+            .unwrap(),
+        walled_maze(),
+    ];
+
+    let mut buffers = SearchBuffers::default();
+    for maze in &mazes {
+        let heuristic = DiagonalHeuristic::new(maze);
+        let generator = EightNeighborGenerator::new(maze);
+        let (start, goal) = (maze.start(), maze.goal());
+        let expected = a_star(start, goal, &heuristic, &generator, noop_sender());
+
+        let mut search = Search::with_buffers(
+            buffers,
+            start,
+            goal,
+            &heuristic,
+            &generator,
+            noop_sender(),
+            1.0,
+            1.0,
+        );
+        assert_eq!(search.g(goal), None);
+        search.run_until(&SearchLimits::default());
+        let (info, reused) = search.into_parts();
+        buffers = reused;
+
+        assert_eq!(info.nodes, expected.nodes);
+        assert_eq!(info.path.unwrap().path, expected.path.unwrap().path);
+    }
+}
+
+#[test]
+fn test_best_first_reuses_buffers() {
+    let mut rng = Lcg::new(31);
+    let mazes: Vec<_> = (0..4)
+        .map(|i| {
+            let size = (12 + 5 * i, 9 + 3 * i);
+            let goal = Position::new(size.0 - 1, size.1 - 1);
+            random_maze(&mut rng, size, Position::ZERO, goal, 25, 30)
+        })
+        .collect();
+
+    let mut best_first = BestFirst::new(1.0, 1.0);
+    for (i, maze) in mazes.iter().chain(mazes.iter().rev()).enumerate() {
+        // a queue of another kind must not reuse the buffers
+        best_first.queue = [QueueKind::Binary, QueueKind::Bucket][i / 3 % 2];
+        let heuristic = DiagonalHeuristic::new(maze);
+        let generator = EightNeighborGenerator::new(maze);
+        let (start, goal) = (maze.start(), maze.goal());
+        let expected = a_star(start, goal, &heuristic, &generator, noop_sender());
+        let info = best_first.search(start, goal, &heuristic, &generator, noop_sender());
+
+        assert_eq!(info.nodes, expected.nodes);
+        assert_eq!(info.path.map(|p| p.path), expected.path.map(|p| p.path));
+    }
+}
+
+#[test]
+fn test_bucket_queue_far_keys() {
+    // one bucket per cost would need 2^37 of them
+    let mut queue = BucketQueue::new(1000);
+    let mut rng = Lcg::new(5);
+    let mut keys: Vec<_> = (0..1000)
+        .map(|i| QueueKey::new(i * 100_000_000, rng.next() as u64))
+        .collect();
+    for (item, &key) in keys.iter().enumerate() {
+        queue.push_or_decrease(item, key);
+    }
+    // far items lowered near the popped ones must still come out in order
+    queue.pop();
+    for item in [999, 500, 2] {
+        keys[item].cost = 100_000_000 + 1;
+        queue.push_or_decrease(item, keys[item]);
+    }
+    assert!(queue.buckets() <= 1 << 16);
+
+    let mut expected: Vec<_> = (1..1000).map(|item| (keys[item], item)).collect();
+    expected.sort();
+    for (key, item) in expected {
+        assert_eq!(queue.pop(), Some((item, key)));
+        assert!(queue.buckets() <= 1 << 16);
+    }
+    assert_eq!(queue.pop(), None);
+}
+
+#[test]
+fn test_priority_queues() {
+    let kinds = [
+        QueueKind::Binary,
+        QueueKind::Quaternary,
+        QueueKind::Pairing,
+        QueueKind::Radix,
+        QueueKind::Bucket,
+    ];
+    for kind in kinds {
+        let mut rng = Lcg::new(777);
+
+        let mut queue = kind.build();
+        queue.reset(50);
+        let mut keys: [Option<QueueKey>; 50] = [None; 50];
+        let lowest = |keys: &[Option<QueueKey>]| {
+            (0..keys.len())
+                .filter_map(|item| keys[item].map(|key| (key, item)))
+                .min()
+        };
+        let mut last = 0;
+        for round in 0..2000 {
+            if round % 3 == 2 {
+                let expected = lowest(&keys).map(|(key, item)| (item, key));
+                assert_eq!(queue.peek(), expected, "{kind:?}");
+                assert_eq!(queue.pop(), expected, "{kind:?}");
+                if let Some((item, key)) = expected {
+                    assert!(!queue.contains(item), "{kind:?}");
+                    keys[item] = None;
+                    last = key.cost;
+                }
+                continue;
+            }
+
+            // mostly costs no lower than the last popped one, as the monotone queues expect,
+            // with many ties
+            let item = rng.next() as usize % 50;
+            let cost = match rng.next() {
+                0..10 => last.saturating_sub(rng.next() as Cost * 100),
+                _ => last + rng.next() as Cost * 100,
+            };
+            let key = QueueKey::new(cost, rng.next() as u64 % 3);
+            let lowered = keys[item].is_none_or(|k| key < k);
+            assert_eq!(queue.push_or_decrease(item, key), lowered, "{kind:?}");
+            if lowered {
+                keys[item] = Some(key);
+            }
+            assert_eq!(queue.key(item), keys[item], "{kind:?}");
+            assert_eq!(queue.len(), keys.iter().flatten().count(), "{kind:?}");
+        }
+
+        while let Some(expected) = lowest(&keys) {
+            assert_eq!(queue.pop(), Some((expected.1, expected.0)), "{kind:?}");
+            keys[expected.1] = None;
+        }
+        assert_eq!(queue.pop(), None, "{kind:?}");
+
+        queue.push_or_decrease(3, QueueKey::new(1, 0));
+        queue.reset(100);
+        assert!(queue.is_empty() && !queue.contains(3), "{kind:?}");
+        queue.push_or_decrease(99, QueueKey::new(2, 0));
+        assert_eq!(queue.peek(), Some((99, QueueKey::new(2, 0))), "{kind:?}");
+        queue.grow(200);
+        queue.push_or_decrease(150, QueueKey::new(1, 0));
+        assert_eq!(queue.pop(), Some((150, QueueKey::new(1, 0))), "{kind:?}");
+        assert_eq!(queue.peek(), Some((99, QueueKey::new(2, 0))), "{kind:?}");
+
+        // emptied, the queue still accepts keys between the last popped and the next pushed
+        queue.pop();
+        queue.push_or_decrease(1, QueueKey::new(30, 0));
+        queue.push_or_decrease(2, QueueKey::new(20, 0));
+        queue.push_or_decrease(3, QueueKey::new(25, 0));
+        assert_eq!(queue.pop(), Some((2, QueueKey::new(20, 0))), "{kind:?}");
+    }
+}
+
+#[test]
+fn test_best_first_queues() {
+    let mut rng = Lcg::new(31);
+
+    for _ in 0..6 {
+        let (width, height) = (40, 30);
+        let start = Position::new(0, 0);
+        let goal = Position::new(width - 1, height - 1);
+        let maze = random_maze(&mut rng, (width, height), start, goal, 25, 15);
+        let heuristic = OctileHeuristic::new(&maze);
+        let generator = EightNeighborGenerator::new(&maze);
+        let optimal = a_star(start, goal, &heuristic, &generator, noop_sender());
+
+        let tie_breaks = [
+            TieBreak::HigherG,
+            TieBreak::LowerH,
+            TieBreak::Lifo,
+            TieBreak::Fifo,
+            TieBreak::CrossProduct,
+        ];
+        for tie_break in tie_breaks {
+            for (depth_weight, heuristic_weight) in [(1.0, 1.0), (1.0, 0.0), (1.0, 3.0), (0.0, 1.0)]
+            {
+                let search = |queue| {
+                    BestFirst::new(depth_weight, heuristic_weight)
+                        .with_tie_break(tie_break)
+                        .with_queue(queue)
+                        .search(start, goal, &heuristic, &generator, noop_sender())
+                };
+                let expected = search(QueueKind::Quaternary);
+                if depth_weight == heuristic_weight || heuristic_weight == 0.0 {
+                    assert_eq!(
+                        expected.path.as_ref().map(|p| p.cost),
+                        optimal.path.as_ref().map(|p| p.cost)
+                    );
+                }
+
+                // the policy alone decides the order, whatever the queue
+                for queue in [
+                    QueueKind::Binary,
+                    QueueKind::Pairing,
+                    QueueKind::Radix,
+                    QueueKind::Bucket,
+                ] {
+                    let info = search(queue);
+                    assert_eq!(info.nodes, expected.nodes, "{queue:?} {tie_break:?}");
+                    assert_eq!(info.max_length, expected.max_length);
+                    assert_eq!(
+                        info.path.map(|p| p.path),
+                        expected.path.as_ref().map(|p| p.path.clone()),
+                        "{queue:?} {tie_break:?}"
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_bidirectional_frontiers() {
+    let maze = walled_maze();
+    let heuristic = DiagonalHeuristic::new(&maze);
+    let generator = JpsGenerator::new(&maze);
+    let (tx, rx) = channel();
+
+    let info = BidirectionalAStar.search(maze.start(), maze.goal(), &heuristic, &generator, tx);
+    assert!(info.path.is_some());
+
+    let frontiers: Vec<_> = rx
+        .try_iter()
+        .filter_map(|m| match m {
+            Message::FrontierEnqueued(frontier, _, _) => Some(frontier),
+            _ => None,
+        })
+        .collect();
+    assert!(frontiers.contains(&Frontier::Forward));
+    assert!(frontiers.contains(&Frontier::Backward));
+}
+
+#[test]
+fn test_ara_improvements() {
+    let maze = walled_maze();
+    let heuristic = DiagonalHeuristic::new(&maze);
+    let generator = JpsGenerator::new(&maze);
+    let (tx, rx) = channel();
+
+    let info = AraStar::new(5.0, 1.0).search(maze.start(), maze.goal(), &heuristic, &generator, tx);
+    let cost = info.path.unwrap().cost;
+
+    let improved: Vec<_> = rx
+        .try_iter()
+        .filter_map(|m| match m {
+            Message::Improved(path, bound) => Some((path.cost, bound)),
+            _ => None,
+        })
+        .collect();
+    assert!(!improved.is_empty());
+    assert!(improved.windows(2).all(|w| w[1].0 < w[0].0));
+    assert!(improved.iter().all(|&(c, b)| b >= 1.0 && c >= cost));
+    assert_eq!(improved.last().unwrap().0, cost);
+}
+
+#[test]
+fn test_theta_star() {
+    let maze = walled_maze();
+    let optimal = search_cost(&Dijkstra, &maze);
+
+    for lazy in [false, true] {
+        let heuristic = DiagonalHeuristic::new(&maze);
+        let generator = JpsGenerator::new(&maze);
+        let search = |densify| {
+            let info = if lazy {
+                LazyThetaStar::new(&maze, DiagonalMovement::default(), densify).search(
+                    maze.start(),
+                    maze.goal(),
+                    &heuristic,
+                    &generator,
+                    noop_sender(),
+                )
+            } else {
+                ThetaStar::new(&maze, DiagonalMovement::default(), densify).search(
+                    maze.start(),
+                    maze.goal(),
+                    &heuristic,
+                    &generator,
+                    noop_sender(),
+                )
+            };
+            info.path.unwrap()
+        };
+
+        let waypoints = search(false);
+        assert!(waypoints.cost < optimal);
+        assert!(waypoints.path.windows(2).all(|w| maze.line_of_sight(
+            w[0],
+            w[1],
+            DiagonalMovement::default()
+        )));
+        let length: Cost = waypoints
+            .path
+            .windows(2)
+            .map(|w| maze.line_cost(w[0], w[1]))
+            .sum();
+        assert_eq!(length, waypoints.cost);
+
+        let cells = search(true);
+        assert_eq!(cells.cost, waypoints.cost);
+        assert!(cells.path.iter().all(|&p| maze.is_free(p)));
+        assert!(
+            cells
+                .path
+                .windows(2)
+                .all(|w| (w[1] - w[0]).abs().max_element() == 1)
+        );
+    }
+
+    // 4-connected, the segments between waypoints never cut through a corner
+    let movement = DiagonalMovement::Never;
+    let info = ThetaStar::new(&maze, movement, true).search(
+        maze.start(),
+        maze.goal(),
+        &DiagonalHeuristic::with_movement(&maze, movement),
+        &FourNeighborGenerator::new(&maze),
+        noop_sender(),
+    );
+    let cells = info.path.unwrap().path;
+    assert!(
+        cells
+            .windows(2)
+            .all(|w| (w[1] - w[0]).abs().element_sum() == 1)
+    );
+}
+
+/// Children two steps away towards growing coordinates, never next to the node nor back
+/// towards the node they were generated from.
+struct Strides<'a>(&'a Maze);
+
+impl ChildrenGenerator for Strides<'_> {
+    fn generate_children(&self, current: Position, _parent: Option<Position>) -> Vec<Child> {
+        let movement = DiagonalMovement::default();
+        EightNeighborGenerator::new(self.0)
+            .generate_children(current, None)
+            .into_iter()
+            .filter_map(|c| {
+                let direction = c.node - current;
+                let far = c.node + direction;
+                (direction.min_element() >= 0
+                    && self.0.is_free(far)
+                    && movement.allows(self.0, c.node, direction))
+                .then(|| Child::new(far, c.weight + self.0.step_cost(c.node, far)))
+            })
+            .collect()
+    }
+
+    fn reconstruct_path(&self, path: MazePathRef) -> PathInfo {
+        EightNeighborGenerator::new(self.0).reconstruct_path(path)
+    }
+}
+
+#[test]
+fn test_lazy_theta_star_far_children() {
+    // a node whose assumed parent is hidden used to look for its new parent among its own
+    // children, none of which is next to it here
+    let mut rng = Lcg::new(909);
+    let mut found = 0;
+
+    for _ in 0..40 {
+        let start = Position::new(0, 0);
+        let goal = Position::new(14, 12);
+        let maze = random_maze(&mut rng, (15, 13), start, goal, 30, 0);
+
+        let info = LazyThetaStar::new(&maze, DiagonalMovement::default(), false).search(
+            start,
+            goal,
+            &OctileHeuristic::new(&maze),
+            &Strides(&maze),
+            noop_sender(),
+        );
+        if let Some(path) = info.path {
+            let length: Cost = path
+                .path
+                .windows(2)
+                .map(|w| maze.line_cost(w[0], w[1]))
+                .sum();
+            assert_eq!(length, path.cost);
+            found += 1;
+        }
+    }
+    assert!(found > 0);
+}
+
+#[test]
+fn test_sma_memory_bound() {
+    let mut maze = walled_maze();
+    for y in 0..20 {
+        if y % 4 == 1 {
+            maze.set(Position::new(16, y), true).unwrap();
+        }
+    }
+    let heuristic = DiagonalHeuristic::new(&maze);
+    let generator = JpsGenerator::new(&maze);
+    let optimal = Dijkstra
+        .search(
+            maze.start(),
+            maze.goal(),
+            &heuristic,
+            &generator,
+            noop_sender(),
+        )
+        .path
+        .unwrap();
+
+    for cap in [3, 10, 15, 20, 25] {
+        let info = SmaStar::new(cap).search(
+            maze.start(),
+            maze.goal(),
+            &heuristic,
+            &generator,
+            noop_sender(),
+        );
+        assert!(info.max_length <= cap);
+        if optimal.path.len() <= cap {
+            assert_eq!(info.path.map(|p| p.cost), Some(optimal.cost), "cap {cap}");
+        }
+    }
+}
+
+#[test]
+fn test_sma_tiny_memory() {
+    // dead ends forgotten by a parent used to be regenerated as promising, looping forever
+    let mut rng = Lcg::new(11);
+    let start = Position::new(0, 0);
+    let goal = Position::new(5, 4);
+
+    for cap in 2..8 {
+        for _ in 0..10 {
+            let maze = random_maze(&mut rng, (6, 5), start, goal, 25, 15);
+            let heuristic = DiagonalHeuristic::new(&maze);
+            let generator = JpsGenerator::new(&maze);
+            let optimal = a_star(start, goal, &NoHeuristic, &generator, noop_sender());
+
+            let info = SmaStar::new(cap).search(start, goal, &heuristic, &generator, noop_sender());
+            assert!(info.max_length <= cap);
+            match (info.path, optimal.path) {
+                (Some(path), Some(optimal)) if optimal.path.len() <= cap => {
+                    assert_eq!(path.cost, optimal.cost)
+                }
+                (Some(path), Some(optimal)) => assert!(path.cost >= optimal.cost),
+                (Some(_), None) => panic!("path found on an unsolvable maze"),
+                (None, optimal) => {
+                    assert_eq!(info.stop, StopReason::Exhausted);
+                    assert!(optimal.is_none_or(|o| o.path.len() > cap));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_jps_large_empty_maze() {
+    // runs on a thread with the default stack size, which used to overflow while jumping
+    let handle = std::thread::spawn(|| {
+        let maze = Maze::new(10_000, 10_000, Position::new(0, 0), Position::new(9_999, 0));
+        let heuristic = DiagonalHeuristic::new(&maze);
+        let generator = JpsGenerator::new(&maze);
+        a_star(
+            maze.start(),
+            maze.goal(),
+            &heuristic,
+            &generator,
+            noop_sender(),
+        )
+    });
+
+    let path = handle.join().unwrap().path.unwrap();
+    assert_eq!(path.path_len(), 10_000);
+    assert_eq!(path.cost, 9_999 * COST_SCALE);
+}
+
+#[test]
+fn test_tie_break() {
+    let (start, goal) = (Position::new(0, 0), Position::new(19, 9));
+    let maze = MazeBuilder::new()
+        .start(start)
+        .goal(goal)
+        .width(20)
+        .height(10)
+        .build()
+        .unwrap();
+    let heuristic = OctileHeuristic::new(&maze);
+    let generator = EightNeighborGenerator::new(&maze);
+    let search = |tie_break| {
+        BestFirst::new(1.0, 1.0).with_tie_break(tie_break).search(
+            start,
+            goal,
+            &heuristic,
+            &generator,
+            noop_sender(),
+        )
+    };
+
+    // every octile path is optimal on an empty maze, the deepest first follows one of them
+    let deepest = search(TieBreak::HigherG);
+    assert_eq!(deepest.nodes, 20);
+    assert!(search(TieBreak::Fifo).nodes > deepest.nodes);
+
+    // the path closest to the straight line stays within a cell of it
+    let line = goal - start;
+    let path = search(TieBreak::CrossProduct).path.unwrap();
+    assert_eq!(path.cost, deepest.path.unwrap().cost);
+    assert!(path.path.iter().all(|&pos| {
+        let offset = pos - start;
+        let cross = (offset.x * line.y - offset.y * line.x) as f64;
+        cross.abs() <= line.as_dvec2().length()
+    }));
+}
@@ -0,0 +1,17 @@
+use crate::bitgrid::BitGrid;
+
+#[test]
+fn test_ones() {
+    let mut grid = BitGrid::new(65, 2);
+    grid.set(64, 0, true);
+    grid.set(0, 1, true);
+    grid.set(3, 1, true);
+
+    assert_eq!(grid.stride(), 2);
+    assert_eq!(grid.count_ones(), 3);
+    assert_eq!(grid.ones().collect::<Vec<_>>(), &[(64, 0), (0, 1), (3, 1)]);
+
+    grid.set(64, 0, false);
+    assert!(!grid.get(64, 0));
+    assert_eq!(grid.row(0), &[0, 0]);
+}
@@ -1,8 +1,10 @@
-use std::f64::consts::SQRT_2;
-
 use crate::{
     algorithm::Child,
-    generator::{ChildrenGenerator, JpsGenerator},
+    cost::{COST_SCALE, Cost, DIAGONAL_COST},
+    generator::{
+        ChildrenGenerator, DiagonalMovement, EightNeighborGenerator, FourNeighborGenerator,
+        Jps4Generator, JpsGenerator,
+    },
     maze_builder::MazeBuilder,
     position::Position,
 };
@@ -15,9 +17,9 @@ fn test_generate_no_parent_walls() {
     let children = generator.generate_children(maze.start(), None);
     assert_eq!(children.len(), 3);
 
-    contains_child(&children, Position::new(1, 0), 1.0);
-    contains_child(&children, Position::new(0, 1), 1.0);
-    contains_child(&children, Position::new(1, 1), SQRT_2);
+    contains_child(&children, Position::new(1, 0), COST_SCALE);
+    contains_child(&children, Position::new(0, 1), COST_SCALE);
+    contains_child(&children, Position::new(1, 1), DIAGONAL_COST);
 }
 
 #[test]
@@ -28,14 +30,106 @@ fn test_generate_no_parent() {
     let children = generator.generate_children((1, 1).into(), None);
     assert_eq!(children.len(), 8);
 
-    contains_child(&children, Position::new(1, 0), 1.0);
-    contains_child(&children, Position::new(0, 1), 1.0);
-    contains_child(&children, Position::new(1, 2), 1.0);
-    contains_child(&children, Position::new(2, 1), 1.0);
-    contains_child(&children, Position::new(0, 0), SQRT_2);
-    contains_child(&children, Position::new(2, 0), SQRT_2);
-    contains_child(&children, Position::new(2, 2), SQRT_2);
-    contains_child(&children, Position::new(0, 2), SQRT_2);
+    contains_child(&children, Position::new(1, 0), COST_SCALE);
+    contains_child(&children, Position::new(0, 1), COST_SCALE);
+    contains_child(&children, Position::new(1, 2), COST_SCALE);
+    contains_child(&children, Position::new(2, 1), COST_SCALE);
+    contains_child(&children, Position::new(0, 0), DIAGONAL_COST);
+    contains_child(&children, Position::new(2, 0), DIAGONAL_COST);
+    contains_child(&children, Position::new(2, 2), DIAGONAL_COST);
+    contains_child(&children, Position::new(0, 2), DIAGONAL_COST);
+}
+
+#[test]
+fn test_generate_four_and_eight_neighbors() {
+    let maze = maze_builder().add_wall((1, 0).into()).build().unwrap();
+
+    let four = FourNeighborGenerator::new(&maze).generate_children((1, 1).into(), None);
+    assert_eq!(four.len(), 3);
+    assert!(!contains_child(&four, Position::new(1, 0), COST_SCALE));
+    assert!(contains_child(&four, Position::new(1, 2), COST_SCALE));
+
+    let eight =
+        EightNeighborGenerator::new(&maze).generate_children((1, 1).into(), Some((0, 0).into()));
+    assert_eq!(eight.len(), 7);
+    assert!(contains_child(&eight, Position::new(0, 0), DIAGONAL_COST));
+}
+
+#[test]
+fn test_jps4_jumps() {
+    let maze = maze_builder().add_wall((4, 3).into()).build().unwrap();
+    let generator = Jps4Generator::new(&maze);
+
+    // moving right along row 4, the wall above (4, 3) opens (5, 3) up
+    let children = generator.generate_children((1, 4).into(), Some((0, 4).into()));
+    assert_eq!(children, [Child::new(Position::new(5, 4), 4 * COST_SCALE)]);
+
+    // moving down column 5, the first scan to the right finds nothing until the goal row
+    let children = generator.generate_children((5, 4).into(), Some((5, 3).into()));
+    assert!(contains_child(
+        &children,
+        Position::new(5, 9),
+        5 * COST_SCALE
+    ));
+}
+
+#[test]
+fn test_diagonal_movement() {
+    // walls touching at a corner, squeezing from (1, 1) to (2, 2)
+    let maze = maze_builder()
+        .add_wall((2, 1).into())
+        .add_wall((1, 2).into())
+        .add_wall((3, 2).into())
+        .build()
+        .unwrap();
+    let from = Position::new(1, 1);
+    let squeeze = Position::new(1, 1);
+    let beside_one = Position::new(1, -1);
+    let free = Position::new(-1, -1);
+
+    let cases = [
+        (DiagonalMovement::Always, [true, true, true]),
+        (DiagonalMovement::Never, [false, false, false]),
+        (DiagonalMovement::IfNoObstacles, [false, false, true]),
+        (DiagonalMovement::IfAtMostOneObstacle, [false, true, true]),
+    ];
+    for (movement, expected) in cases {
+        let allowed = [squeeze, beside_one, free].map(|d| movement.allows(&maze, from, d));
+        assert_eq!(allowed, expected, "{movement:?}");
+        assert!(movement.allows(&maze, from, Position::new(0, 1)));
+
+        let children = JpsGenerator::with_movement(&maze, movement).generate_children(from, None);
+        assert_eq!(
+            contains_child(&children, Position::new(2, 2), DIAGONAL_COST),
+            expected[0]
+        );
+    }
+}
+
+#[test]
+fn test_jps_does_not_squeeze_between_corners() {
+    // a diagonal wall from (0, 3) to (3, 0) separates the start from the goal
+    let mut builder = maze_builder();
+    for i in 0..4 {
+        builder = builder.add_wall((i, 3 - i).into());
+    }
+    let maze = builder.build().unwrap();
+
+    let generator = JpsGenerator::new(&maze);
+    let children = generator.generate_children((1, 1).into(), None);
+    assert!(!contains_child(
+        &children,
+        Position::new(2, 2),
+        DIAGONAL_COST
+    ));
+
+    let generator = JpsGenerator::with_movement(&maze, DiagonalMovement::Always);
+    let children = generator.generate_children((1, 1).into(), None);
+    assert!(contains_child(
+        &children,
+        Position::new(2, 2),
+        DIAGONAL_COST
+    ));
 }
 
 fn maze_builder() -> MazeBuilder {
@@ -46,6 +140,6 @@ fn maze_builder() -> MazeBuilder {
         .goal((9, 9).into())
 }
 
-fn contains_child(children: &[Child], pos: Position, weight: f64) -> bool {
+fn contains_child(children: &[Child], pos: Position, weight: Cost) -> bool {
     children.iter().any(|c| c.node == pos && c.weight == weight)
 }
@@ -0,0 +1,253 @@
+use std::sync::Arc;
+
+use super::{Lcg, assert_same_cost, random_maze};
+use crate::{
+    algorithm::{Dijkstra, SearchAlgorithm, a_star},
+    channel::noop_sender,
+    cost::{COST_SCALE, Cost, DIAGONAL_COST, scale_cost},
+    generator::{ChildrenGenerator, DiagonalMovement, EightNeighborGenerator},
+    heuristics::{
+        ChebyshevHeuristic, DEFAULT_TIE_BREAK, DiagonalHeuristic, EuclideanHeuristic,
+        LandmarkHeuristic, LandmarkSelection, Landmarks, ManhattanHeuristic, MazeHeuristic,
+        OctileHeuristic, WeightedHeuristic, check_heuristic,
+    },
+    maze::Maze,
+    maze_builder::MazeBuilder,
+    position::Position,
+};
+
+#[test]
+fn test_distance_heuristics() {
+    let maze = MazeBuilder::new()
+        .start(Position::new(0, 0))
+        .goal(Position::new(1, 2))
+        .width(8)
+        .height(8)
+        .add_cost(Position::new(4, 4), 3.0)
+        .build()
+        .unwrap();
+    let node = Position::new(5, 5);
+    let h = |heuristic: &dyn MazeHeuristic| heuristic.compute_heuristic(node);
+
+    let octile = 3 * DIAGONAL_COST + COST_SCALE;
+    assert_eq!(h(&OctileHeuristic::new(&maze)), octile);
+    assert_eq!(
+        h(&DiagonalHeuristic::new(&maze)),
+        scale_cost(octile, DEFAULT_TIE_BREAK)
+    );
+    assert_eq!(
+        h(&DiagonalHeuristic::new(&maze).with_tie_break(1.0)),
+        octile
+    );
+    assert_eq!(
+        h(&OctileHeuristic::with_movement(
+            &maze,
+            DiagonalMovement::Never
+        )),
+        7 * COST_SCALE
+    );
+    assert_eq!(h(&ManhattanHeuristic::new(&maze)), 7 * COST_SCALE);
+    // slightly below the straight-line distance, to stay admissible after rounding
+    let euclidean = h(&EuclideanHeuristic::new(&maze));
+    assert!((5 * COST_SCALE - 10..5 * COST_SCALE).contains(&euclidean));
+    assert_eq!(h(&ChebyshevHeuristic::new(&maze)), 4 * COST_SCALE);
+    assert_eq!(
+        h(&WeightedHeuristic::new(ManhattanHeuristic::new(&maze), 1.5)),
+        10 * COST_SCALE + COST_SCALE / 2
+    );
+    assert_eq!(
+        ChebyshevHeuristic::new(&maze).compute_heuristic(maze.goal()),
+        0
+    );
+}
+
+#[test]
+fn test_landmark_heuristic() {
+    let mut rng = Lcg::new(17);
+
+    for round in 0..24 {
+        let (width, height) = (23, 17);
+        let start = rng.position(width, height);
+        let goal = rng.position(width, height);
+        let maze = random_maze(
+            &mut rng,
+            (width, height),
+            start,
+            goal,
+            30,
+            if round % 2 == 1 { 10 } else { 0 },
+        );
+
+        let movement = match round % 3 {
+            0 => DiagonalMovement::Never,
+            1 => DiagonalMovement::IfNoObstacles,
+            _ => DiagonalMovement::IfAtMostOneObstacle,
+        };
+        let selection = if round % 4 < 2 {
+            LandmarkSelection::Farthest
+        } else {
+            LandmarkSelection::Corners
+        };
+        let landmarks = Arc::new(Landmarks::new(&maze, 4, selection, movement));
+        assert!(landmarks.landmarks().len() <= 4);
+        assert!(landmarks.matches(&maze));
+
+        let heuristic = LandmarkHeuristic::new(&maze, landmarks);
+        let generator = EightNeighborGenerator::with_movement(&maze, movement);
+
+        for x in 0..width {
+            for y in 0..height {
+                let node = Position::new(x, y);
+                if !maze.is_free(node) {
+                    continue;
+                }
+                let h = heuristic.compute_heuristic(node);
+
+                // consistent
+                for child in generator.generate_children(node, None) {
+                    assert!(h <= child.weight + heuristic.compute_heuristic(child.node));
+                }
+
+                // admissible
+                if (x + y) % 7 == 0 {
+                    let info = Dijkstra.search(
+                        node,
+                        goal,
+                        &DiagonalHeuristic::default(),
+                        &generator,
+                        noop_sender(),
+                    );
+                    if let Some(path) = info.path {
+                        assert!(h <= path.cost);
+                    }
+                }
+            }
+        }
+
+        let octile = DiagonalHeuristic::with_movement(&maze, movement);
+        let with_landmarks = a_star(start, goal, &heuristic, &generator, noop_sender());
+        let without = a_star(start, goal, &octile, &generator, noop_sender());
+        assert_same_cost(&with_landmarks.path, &without.path);
+    }
+}
+
+#[test]
+fn test_corner_landmarks() {
+    let maze = MazeBuilder::new()
+        .start(Position::new(0, 0))
+        .goal(Position::new(9, 5))
+        .width(10)
+        .height(6)
+        .add_wall(Position::new(9, 0))
+        .build()
+        .unwrap();
+
+    let landmarks = Landmarks::new(
+        &maze,
+        4,
+        LandmarkSelection::Corners,
+        DiagonalMovement::Never,
+    );
+    assert_eq!(
+        landmarks.landmarks(),
+        [
+            Position::new(0, 0),
+            Position::new(7, 0),
+            Position::new(9, 5),
+            Position::new(2, 5),
+        ]
+    );
+    assert_eq!(
+        landmarks.distance(0, Position::new(9, 5)),
+        Some(14 * COST_SCALE)
+    );
+}
+
+#[test]
+fn test_far_landmarks() {
+    // farther than a u32 of fixed-point cost can hold
+    let mut maze = Maze::new(5_000, 1, Position::new(0, 0), Position::new(4_999, 0));
+    for x in 0..5_000 {
+        maze.set_cost(Position::new(x, 0), 100.0).unwrap();
+    }
+
+    let landmarks = Landmarks::new(
+        &maze,
+        1,
+        LandmarkSelection::Corners,
+        DiagonalMovement::Never,
+    );
+    let distance = 4_999 * scale_cost(COST_SCALE, 100.0);
+    assert!(distance > u32::MAX as Cost);
+    assert_eq!(landmarks.distance(0, maze.goal()), Some(distance));
+}
+
+/// Admissible but not consistent: the octile distance on even columns only.
+struct EvenColumns(OctileHeuristic);
+
+impl MazeHeuristic for EvenColumns {
+    fn compute_heuristic(&self, node: Position) -> Cost {
+        if node.x % 2 == 0 {
+            self.0.compute_heuristic(node)
+        } else {
+            0
+        }
+    }
+}
+
+#[test]
+fn test_check_heuristic() {
+    let mut builder = MazeBuilder::new()
+        .start(Position::new(0, 0))
+        .goal(Position::new(10, 7))
+        .width(14)
+        .height(9);
+    for y in 0..7 {
+        builder = builder.add_wall(Position::new(5, y));
+    }
+    builder = builder
+        .add_wall(Position::new(12, 0))
+        .add_wall(Position::new(12, 1))
+        .add_wall(Position::new(13, 1))
+        .add_cost(Position::new(8, 8), 4.0);
+    let maze = builder.build().unwrap();
+    let movement = DiagonalMovement::IfAtMostOneObstacle;
+    let generator = EightNeighborGenerator::with_movement(&maze, movement);
+
+    let report = check_heuristic(
+        &maze,
+        &generator,
+        &OctileHeuristic::with_movement(&maze, movement),
+    );
+    assert_eq!(report.cells, 14 * 9 - 10);
+    // the corner behind the walls is cut off
+    assert_eq!(report.reachable, report.cells - 1);
+    assert!(report.is_admissible());
+    assert!(report.is_consistent());
+
+    let report = check_heuristic(
+        &maze,
+        &generator,
+        &DiagonalHeuristic::with_movement(&maze, movement),
+    );
+    assert!(!report.is_admissible());
+    assert!(!report.is_consistent());
+
+    let report = check_heuristic(
+        &maze,
+        &generator,
+        &EvenColumns(OctileHeuristic::with_movement(&maze, movement)),
+    );
+    assert!(report.is_admissible());
+    assert!(!report.is_consistent());
+    let worst = report.worst_inconsistency().unwrap();
+    assert_eq!(worst.from.x % 2, 0);
+    assert_eq!(worst.to.x % 2, 1);
+
+    let report = check_heuristic(&maze, &generator, &ManhattanHeuristic::new(&maze));
+    assert!(!report.is_admissible());
+    assert!(!report.is_consistent());
+    let worst = report.worst_overestimate().unwrap();
+    assert!(worst.heuristic > worst.cost);
+    assert!(report.to_string().contains("Not admissible"));
+}
@@ -1,4 +1,9 @@
-use crate::{maze::Maze, position::Position};
+use crate::{
+    cost::{COST_SCALE, DIAGONAL_COST},
+    generator::DiagonalMovement,
+    maze::Maze,
+    position::{PosFunctions, Position, line},
+};
 
 #[test]
 fn test_creation() {
@@ -9,3 +14,125 @@ fn test_creation() {
     assert_eq!(maze.goal(), Position::new(2, 3));
     assert_eq!(maze.walls().count(), 0);
 }
+
+#[test]
+fn test_set_walls() {
+    let mut maze = Maze::new(130, 3, Position::new(0, 0), Position::new(129, 2));
+    maze.set(Position::new(63, 1), true).unwrap();
+    maze.set(Position::new(64, 1), true).unwrap();
+    maze.set(Position::new(129, 2), true).unwrap();
+    maze.set(Position::new(64, 1), false).unwrap();
+
+    assert!(maze.is_wall(Position::new(63, 1)));
+    assert!(maze.is_free(Position::new(64, 1)));
+    assert!(maze.is_wall(Position::new(129, 2)));
+    assert!(!maze.is_free(Position::new(130, 2)));
+    assert!(!maze.is_wall(Position::new(130, 2)));
+    assert!(maze.set(Position::new(0, 3), true).is_err());
+
+    assert_eq!(
+        maze.walls().collect::<Vec<_>>(),
+        &[Position::new(63, 1), Position::new(129, 2)]
+    );
+}
+
+#[test]
+fn test_wall_row() {
+    let mut maze = Maze::new(70, 2, Position::new(0, 0), Position::new(1, 1));
+    maze.set(Position::new(1, 1), true).unwrap();
+    maze.set(Position::new(65, 1), true).unwrap();
+
+    assert_eq!(maze.wall_row(0), &[0, 0]);
+    assert_eq!(maze.wall_row(1), &[0b10, 0b10]);
+}
+
+#[test]
+fn test_wide_maze() {
+    let goal = Position::try_convert(39_999u32, 1u32).unwrap();
+    let mut maze = Maze::new(40_000, 2, Position::new(0, 0), goal);
+    maze.set(Position::new(32_768, 1), true).unwrap();
+
+    assert!(maze.valid(goal));
+    assert!(!maze.valid(Position::new(40_000, 1)));
+    assert!(maze.is_wall(Position::new(32_768, 1)));
+    assert_eq!(
+        maze.walls().collect::<Vec<_>>(),
+        &[Position::new(32_768, 1)]
+    );
+}
+
+#[test]
+fn test_line() {
+    let cells = line(Position::new(0, 0), Position::new(3, 1));
+    assert_eq!(
+        cells,
+        &[
+            Position::new(0, 0),
+            Position::new(1, 0),
+            Position::new(2, 1),
+            Position::new(3, 1)
+        ]
+    );
+    assert_eq!(line(Position::new(2, 2), Position::new(0, 0)).len(), 3);
+    assert_eq!(
+        line(Position::new(1, 1), Position::new(1, 1)),
+        &[Position::new(1, 1)]
+    );
+}
+
+#[test]
+fn test_line_of_sight() {
+    let mut maze = Maze::new(5, 5, Position::new(0, 0), Position::new(4, 4));
+    maze.set(Position::new(2, 0), true).unwrap();
+    maze.set(Position::new(1, 1), true).unwrap();
+
+    let sight = |from, to, movement| maze.line_of_sight(from, to, movement);
+    let movement = DiagonalMovement::IfAtMostOneObstacle;
+    assert!(sight(Position::new(0, 2), Position::new(4, 2), movement));
+    assert!(!sight(Position::new(0, 0), Position::new(4, 0), movement));
+    assert!(!sight(Position::new(0, 2), Position::new(2, 0), movement));
+    assert!(!sight(Position::new(1, 0), Position::new(2, 1), movement));
+    assert!(sight(Position::new(0, 4), Position::new(4, 1), movement));
+
+    assert!(sight(
+        Position::new(1, 0),
+        Position::new(2, 1),
+        DiagonalMovement::Always
+    ));
+    assert!(!sight(
+        Position::new(0, 1),
+        Position::new(1, 2),
+        DiagonalMovement::IfNoObstacles
+    ));
+    assert!(sight(Position::new(0, 1), Position::new(1, 2), movement));
+    assert!(sight(
+        Position::new(0, 4),
+        Position::new(4, 1),
+        DiagonalMovement::Never
+    ));
+    assert!(!sight(
+        Position::new(0, 4),
+        Position::new(3, 1),
+        DiagonalMovement::Never
+    ));
+    assert!(sight(
+        Position::new(0, 2),
+        Position::new(4, 2),
+        DiagonalMovement::Never
+    ));
+}
+
+#[test]
+fn test_line_cost() {
+    let mut maze = Maze::new(5, 5, Position::new(0, 0), Position::new(4, 4));
+    let (from, to) = (Position::new(0, 0), Position::new(3, 4));
+    assert_eq!(maze.line_cost(from, to), 5 * COST_SCALE);
+
+    maze.set_cost(Position::new(1, 1), 3.0).unwrap();
+    let diagonal = (Position::new(0, 0), Position::new(1, 1));
+    assert_eq!(maze.line_cost(diagonal.0, diagonal.1), 2 * DIAGONAL_COST);
+    assert_eq!(
+        maze.line_cost(diagonal.0, diagonal.1),
+        maze.step_cost(diagonal.0, diagonal.1)
+    );
+}
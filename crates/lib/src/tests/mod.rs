@@ -1,3 +1,88 @@
 mod algorithm;
+mod bitgrid;
 mod generator;
+mod heuristics;
 mod maze;
+
+use crate::{
+    cost::Cost,
+    generator::{DiagonalMovement, PathInfo},
+    maze::Maze,
+    maze_builder::MazeBuilder,
+    position::Position,
+};
+
+/// Linear congruential generator, for random mazes that are the same on every run.
+struct Lcg(u32);
+
+impl Lcg {
+    fn new(seed: u32) -> Self {
+        Lcg(seed)
+    }
+
+    /// A number below 100.
+    fn next(&mut self) -> u32 {
+        self.0 = self.0.wrapping_mul(1103515245).wrapping_add(12345);
+        (self.0 >> 16) % 100
+    }
+
+    /// A cell of a `width` by `height` grid.
+    fn position(&mut self, width: i32, height: i32) -> Position {
+        Position::new(self.next() as i32 % width, self.next() as i32 % height)
+    }
+}
+
+/// A `width` by `height` maze where each cell but the start and the goal is a wall with
+/// a chance of `wall_pct` percent, or else costs between 1 and 10.9 with a chance of
+/// `cost_pct` percent.
+fn random_maze(
+    rng: &mut Lcg,
+    (width, height): (i32, i32),
+    start: Position,
+    goal: Position,
+    wall_pct: u32,
+    cost_pct: u32,
+) -> Maze {
+    let mut builder = MazeBuilder::new()
+        .start(start)
+        .goal(goal)
+        .width(width as u32)
+        .height(height as u32);
+    for x in 0..width {
+        for y in 0..height {
+            let pos = Position::new(x, y);
+            if pos == start || pos == goal {
+                continue;
+            }
+            let roll = rng.next();
+            if roll < wall_pct {
+                builder = builder.add_wall(pos);
+            } else if roll < wall_pct + cost_pct {
+                builder = builder.add_cost(pos, 1.0 + rng.next() as f64 / 10.0);
+            }
+        }
+    }
+    builder.build().unwrap()
+}
+
+/// Checks that `path` goes from the start to the goal of `maze` one free cell at a time,
+/// taking only the diagonal steps `movement` allows, and costs as much as its steps.
+fn assert_valid_path(maze: &Maze, movement: DiagonalMovement, path: &PathInfo) {
+    assert_eq!(path.path.first(), Some(&maze.start()));
+    assert_eq!(path.path.last(), Some(&maze.goal()));
+    assert!(path.path.windows(2).all(|w| {
+        let dir = w[1] - w[0];
+        dir.abs().max_element() == 1 && maze.is_free(w[1]) && movement.allows(maze, w[0], dir)
+    }));
+    let walked: Cost = path
+        .path
+        .windows(2)
+        .map(|w| maze.step_cost(w[0], w[1]))
+        .sum();
+    assert_eq!(walked, path.cost);
+}
+
+/// Checks that two searches both found paths of the same cost, or both found none.
+fn assert_same_cost(a: &Option<PathInfo>, b: &Option<PathInfo>) {
+    assert_eq!(a.as_ref().map(|p| p.cost), b.as_ref().map(|p| p.cost));
+}